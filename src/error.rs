@@ -0,0 +1,154 @@
+/*! The things that can go wrong, sorted by what kind of wrong they are so the frontend can tell a typo from a divide by 0 */
+
+use std::fmt::{self, Display, Formatter};
+use std::ops::Range;
+
+/// Where in the input something is, in bytes
+pub type Span = Range<usize>;
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum Error {
+    /// the input doesn't make sense as maths, the span says where, if we know
+    Parse { message: String, span: Option<Span> },
+    /// the maths is fine to write but has no answer, like dividing by 0
+    Domain(String),
+    /// a number got too big for us to keep
+    Overflow(String),
+    UnknownFunction(String),
+    UnknownConstant(String),
+    /// the answer isn't a real number, in a place that needs one
+    NonReal(String),
+    /// the answer has free variables in it, and we can't do that with them
+    Algebra(String),
+    /// a function was given the wrong number of arguments
+    Arity {
+        name: String,
+        expected: String,
+        given: usize,
+    },
+    /// a user function called itself too many times, it probably never stops
+    Recursion { name: String, depth: usize },
+    /// the frontend couldn't read or write something
+    Frontend(String),
+    /// a setting was given a value it can't take, the message says which ones it can
+    InvalidArgument(String),
+}
+
+impl Error {
+    pub fn parse(message: impl Into<String>) -> Self {
+        Error::Parse {
+            message: message.into(),
+            span: None,
+        }
+    }
+
+    pub fn domain(message: impl Into<String>) -> Self {
+        Error::Domain(message.into())
+    }
+
+    pub fn algebra(message: impl Into<String>) -> Self {
+        Error::Algebra(message.into())
+    }
+
+    pub fn non_real(message: impl Into<String>) -> Self {
+        Error::NonReal(message.into())
+    }
+
+    pub fn invalid_argument(message: impl Into<String>) -> Self {
+        Error::InvalidArgument(message.into())
+    }
+
+    /// the `Maths error` bit in `Maths error: Divide by 0`
+    pub fn kind(&self) -> &'static str {
+        match self {
+            Error::Parse { .. } => "Parse error",
+            Error::Domain(_) => "Maths error",
+            Error::Overflow(_) => "Overflow error",
+            Error::UnknownFunction(_) => "Unknown function",
+            Error::UnknownConstant(_) => "Unknown constant",
+            Error::NonReal(_) => "Non-real error",
+            Error::Algebra(_) => "Algebra error",
+            Error::Arity { .. } => "Wrong number of arguments",
+            Error::Recursion { .. } => "Recursion error",
+            Error::Frontend(_) => "Error",
+            Error::InvalidArgument(_) => "Invalid argument",
+        }
+    }
+
+    /// everything after the kind
+    pub fn message(&self) -> String {
+        match self {
+            Error::Parse { message, .. }
+            | Error::Domain(message)
+            | Error::Overflow(message)
+            | Error::NonReal(message)
+            | Error::Algebra(message)
+            | Error::Frontend(message)
+            | Error::InvalidArgument(message) => message.clone(),
+            Error::UnknownFunction(name) | Error::UnknownConstant(name) => name.clone(),
+            Error::Arity {
+                name,
+                expected,
+                given,
+            } => format!(
+                "{} takes {} argument{}, but was given {}",
+                name,
+                expected,
+                if expected == "1" { "" } else { "s" },
+                given
+            ),
+            Error::Recursion { name, depth } => {
+                format!("calls to {} went more than {} deep", name, depth)
+            }
+        }
+    }
+
+    pub fn span(&self) -> Option<Span> {
+        match self {
+            Error::Parse { span, .. } => span.clone(),
+            _ => None,
+        }
+    }
+}
+
+impl Display for Error {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(f, "{}: {}", self.kind(), self.message())
+    }
+}
+
+impl std::error::Error for Error {}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::eval::Environment;
+    use crate::parser::parse_statement;
+
+    fn error_from(input: &str) -> Error {
+        parse_statement(input)
+            .and_then(|statement| statement.execute(&mut Environment::new()))
+            .unwrap_err()
+    }
+
+    #[test]
+    fn displays_kind_then_message() {
+        assert_eq!(format!("{}", Error::domain("Divide by 0")), "Maths error: Divide by 0");
+        let arity = Error::Arity {
+            name: "sin".into(),
+            expected: "1".into(),
+            given: 2,
+        };
+        assert_eq!(format!("{}", arity), "Wrong number of arguments: sin takes 1 argument, but was given 2");
+    }
+
+    #[test]
+    fn errors_know_what_kind_they_are() {
+        assert_eq!(error_from("1/0"), Error::domain("Divide by 0"));
+        // with one argument that would be foo times 2
+        assert_eq!(error_from("foo(2, 3)"), Error::UnknownFunction("foo".into()));
+        assert!(matches!(error_from("asin(2)"), Error::NonReal(_)));
+        assert!(matches!(error_from("x^(2^40)"), Error::Overflow(_)));
+        assert!(matches!(error_from("1 +"), Error::Parse { .. }));
+    }
+}
@@ -1,21 +1,43 @@
+use crate::error::Error;
 use crate::{
-    parser::{BinaryOp, ExprTree, UnaryOp},
+    parser::{BinaryOp, ExprTree, Statement, UnaryOp},
     util::option::{Catch, OrMerge},
     eval::op::calculate_fn::CalculateFn
 };
-use num::rational::Ratio;
+use num::bigint::BigInt;
+use num::rational::{BigRational, Ratio};
+use num::traits::{ToPrimitive, Zero};
+use complex::Complex;
+use op::factorial::{percent, Factorial};
 use op::pow::Pow;
+use polynomial::Polynomial;
+use primes::Factorisation;
+use logarithm::Logarithm;
 use radical::Radical;
+use radical_sum::RadicalSum;
+pub use env::{DecimalMode, Environment};
+use env::{Scope, UserFunction};
+pub use op::calculate_fn::{is_function, AngleMode};
+use serde::{Deserialize, Serialize};
 use std::convert::{TryFrom, TryInto};
 use std::ops::{Mul, Rem};
 
+pub mod big;
+pub mod complex;
+mod diff;
+pub mod env;
+pub mod logarithm;
 mod op;
 mod ord;
+pub mod polynomial;
+pub mod primes;
 pub mod radical;
+pub mod radical_sum;
+pub mod solve;
 
 /// This is a symbolic expression, not like the ones in lisp,
 /// these are for dealing with symbolic numbers like pi and e
-#[derive(Clone, Debug, PartialEq)]
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
 pub struct Symbolic {
     pub coeff: Option<Data>,
     pub symbol: String,
@@ -58,7 +80,7 @@ impl DivisibleBy<u16> for Symbolic {
 }
 
 impl Symbolic {
-    fn as_float(self) -> Result<f64, String> {
+    fn as_float(self) -> Result<f64, Error> {
         Ok(
             f64::try_from(self.coeff.unwrap_or(Data::Int(1)))? * self.symbol.symbol_eval()?
                 + match self.constant {
@@ -85,12 +107,16 @@ impl Symbolic {
 /// The basic data type that all our calculations act on, yes this is very large
 /// for what might be in other implementations a `f64` but in order to preserve
 /// rationals, radicals, and symbols, this needs to be kept.
-#[derive(Clone, Debug, PartialEq)]
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
 pub enum Data {
     /// a whole number
     Int(i64),
     /// a fraction, we use this to avoid floats
     Rational(Ratio<i64>),
+    /// a whole number too big for an `Int`, these get demoted back down whenever they fit again
+    BigInt(BigInt),
+    /// a fraction too big for a `Rational`, likewise demoted whenever it fits
+    BigRational(BigRational),
     /// a fancy form of a square root, see the module itself `radical`
     Radical(Radical),
     /// pi, e, etc, the contents may eventually become a `non_exhaustive` enum to save space
@@ -102,6 +128,16 @@ pub enum Data {
     Float(f64),
     /// see the documentation for `Symbolic`
     Symbolic(Box<Symbolic>),
+    /// something with free variables in it, like `2x + 1`, see the module `polynomial`
+    Polynomial(Polynomial),
+    /// a number with an imaginary part, see the module `complex`
+    Complex(Box<Complex>),
+    /// a sum of radicals, like `1 + √2`, see the module `radical_sum`
+    RadicalSum(Box<RadicalSum>),
+    /// a logarithm that didn't come out exactly, like `log(2)`, see the module `logarithm`
+    Log(Box<Logarithm>),
+    /// a whole number broken down into primes, which is what `factor` gives, see the module `primes`
+    Factorisation(Factorisation),
 }
 
 impl From<i64> for Data {
@@ -126,14 +162,31 @@ impl Data {
     ///flattens any Data value down to a f64
     /// once float-land has been entered, there are only a few cases where we can get out of it.
 
-    fn as_float(self) -> Result<Self, String> {
+    fn as_float(self) -> Result<Self, Error> {
         Ok(match self {
             Self::Float(_) => self,
             Self::Int(n) => Self::Float(n as f64),
             Self::Rational(n) => Self::Float(ratio_as_float(n)),
+            Self::BigInt(n) => Self::Float(n.to_f64().unwrap_or(f64::NAN)),
+            Self::BigRational(n) => Self::Float(big::big_rational_as_float(&n)),
             Self::Symbol(s) => Self::Float(s.symbol_eval().unwrap_or(0.)),
             Self::Symbolic(s) => Self::Float(s.as_float()?),
             Self::Radical(r) => Self::Float(r.as_float()?),
+            Self::RadicalSum(s) => Self::Float(s.as_float()?),
+            Self::Log(l) => Self::Float(l.as_float()?),
+            Self::Polynomial(p) => {
+                return Err(Error::algebra(format!(
+                    "can't make {} into a number, it has free variables in it",
+                    Self::Polynomial(p)
+                )))
+            }
+            Self::Factorisation(f) => Self::from(f.value()).as_float()?,
+            Self::Complex(z) => {
+                return Err(Error::non_real(format!(
+                    "{} isn't a real number",
+                    Self::Complex(z)
+                )))
+            }
         })
     }
 }
@@ -146,21 +199,45 @@ fn ratio_as_float(r: Ratio<i64>) -> f64 {
 ///This trait describes the behaviour of a stringy symbol turning into a number
 
 trait SymbolEval {
-    fn symbol_eval(&self) -> Result<f64, String>;
+    fn symbol_eval(&self) -> Result<f64, Error>;
 }
 
 impl SymbolEval for String {
-    fn symbol_eval(&self) -> Result<f64, String> {
+    fn symbol_eval(&self) -> Result<f64, Error> {
         Ok(match self.as_str() {
             "pi" | "Pi" => std::f64::consts::PI,
             "e" | "E" => std::f64::consts::E,
             "phi" | "Phi" => 1.61803398874989484820458683436563811,
             "sqrt2" | "root2" => std::f64::consts::SQRT_2,
-            e => return Err(format!("constant {} not recognised", e.to_string())),
+            e => return Err(Error::UnknownConstant(e.to_string())),
         })
     }
 }
 
+/// The constants, which can be written as bare words like `pi` or `π`, or the old way with a `:`, like `:pi`.
+/// They all come out under one name each, so `π`, `Pi` and `pi` are all the same thing,
+/// and `tau` is just `2pi` so that it gets all the exact trig that pi does
+pub fn constant(name: &str) -> Option<Data> {
+    let symbol = |name: &str| Data::Symbol(name.to_string());
+    Some(match name {
+        "pi" | "Pi" | "π" => symbol("pi"),
+        "tau" | "Tau" | "τ" => Data::Symbolic(Box::new(Symbolic {
+            coeff: Some(Data::Int(2)),
+            symbol: "pi".to_string(),
+            constant: None,
+        })),
+        "e" | "E" => symbol("e"),
+        "phi" | "Phi" | "ϕ" | "φ" => symbol("phi"),
+        "sqrt2" | "root2" => symbol("sqrt2"),
+        _ => return None,
+    })
+}
+
+/// Is this the name of a constant like pi, rather than a free variable
+pub fn is_constant(name: &str) -> bool {
+    constant(name).is_some()
+}
+
 /// This trait allows us to wrap a calculation for if something is divisible by something else,
 /// which is useful generically for reducing radicals, rationals and symbolic expressions *not the lisp sort*
 trait DivisibleBy<T> {
@@ -204,6 +281,7 @@ impl DivisibleBy<u16> for Data {
             Self::Int(n) => n.divisible_by(divisor as i64),
             Self::Radical(rad) => rad.divisible_by(divisor),
             Self::Rational(rat) => rat.divisible_by(Ratio::from(divisor as i64)),
+            Self::BigInt(n) => (n % divisor).is_zero(),
             Self::Symbolic(s) => s.divisible_by(divisor),
             _ => false,
         }
@@ -226,6 +304,16 @@ impl DivisibleBy<&Data> for Data {
                 Self::Int(m) => n.divisible_by(m),
                 _ => false,
             },
+            Self::BigInt(n) => match &divisor {
+                Self::Int(m) => (n % *m).is_zero(),
+                Self::BigInt(m) => (n % m).is_zero(),
+                _ => false,
+            },
+            // big rationals are only ever divisible by other big rationals, much like their small counterparts
+            Self::BigRational(n) => match &divisor {
+                Self::BigRational(m) => (n / m).is_integer(),
+                _ => false,
+            },
             // Rationals: within our Data enum, Rationals should not be integers in disguise, that should get caught by the reduction step, which means that the implementation provided by the generic above is fine
             Self::Rational(n) => match &divisor {
                 Self::Rational(m) => n.divisible_by(*m),
@@ -242,12 +330,14 @@ impl DivisibleBy<&Data> for Data {
                 Self::Float(m) => n.divisible_by(m),
                 _ => false,
             },
+            Self::Factorisation(f) => Self::from(f.value()).divisible_by(divisor),
+            Self::Polynomial(_) | Self::Complex(_) | Self::RadicalSum(_) | Self::Log(_) => false,
         }
     }
 }
 
 impl TryFrom<Data> for f64 {
-    type Error = String;
+    type Error = Error;
 
     fn try_from(d: Data) -> Result<Self, Self::Error> {
         let f_d = d.as_float()?;
@@ -261,16 +351,56 @@ impl TryFrom<Data> for f64 {
 
 // this is the bit that actually does the maths
 impl ExprTree {
-    pub fn eval(self) -> Result<Data, String> {
+    pub fn eval(self, env: &Environment) -> Result<Data, Error> {
+        self.eval_in(&Scope::global(env))
+    }
+
+    fn eval_in(self, scope: &Scope) -> Result<Data, Error> {
         match self {
+            // the only fractions written straight into an expression are decimals
+            ExprTree::Val(k @ (Data::Rational(_) | Data::BigRational(_)))
+                if scope.env.decimal_mode() == DecimalMode::Float =>
+            {
+                k.as_float()
+            }
             ExprTree::Val(k) => Ok(k),
+            // anything that hasn't been given a value is a free variable
+            ExprTree::Ident(name) => Ok(match scope.get(&name) {
+                Some(value) => value.clone(),
+                // `i` is the imaginary unit, unless it's been given some other value
+                None if name == "i" => Complex::i().into(),
+                None if name == "ans" => return Err(Error::domain("there's no answer yet")),
+                None if name.starts_with('$') => {
+                    return Err(Error::domain(format!("there's no {} in the history", name)))
+                }
+                None => Data::Polynomial(Polynomial::variable(name)),
+            }),
+            ExprTree::Call(name, args) => {
+                let mut args = args
+                    .into_iter()
+                    .map(|arg| arg.eval_in(scope))
+                    .collect::<Result<Vec<_>, _>>()?;
+                match scope.env.function(&name) {
+                    Some(function) => {
+                        let inner = scope.enter(&name, function, args)?;
+                        function.body.clone().eval_in(&inner)
+                    }
+                    // without a function called x, `x(x + 1)` is x times x + 1
+                    None if args.len() == 1 && !is_function(&name) => {
+                        ExprTree::Ident(name).eval_in(scope)? * args.remove(0)
+                    }
+                    None => args.as_slice().calculate_fn(&name, scope.env.angle_mode()),
+                }
+            }
             ExprTree::UNode(op, t) => match op {
-                UnaryOp::Neg => t.eval().map(|x| -x),
-                UnaryOp::Word(w) => t.eval().and_then(|x| x.calculate_fn(&w))
+                UnaryOp::Neg => t.eval_in(scope).map(|x| -x),
+                UnaryOp::Factorial => t.eval_in(scope)?.factorial(),
+                UnaryOp::DoubleFactorial => t.eval_in(scope)?.double_factorial(),
+                UnaryOp::Percent => percent(t.eval_in(scope)?),
             },
             ExprTree::BNode(op, lhs, rhs) => {
-                let l = lhs.eval()?;
-                let r = rhs.eval()?;
+                let l = lhs.eval_in(scope)?;
+                let r = rhs.eval_in(scope)?;
                 match op {
                     BinaryOp::Plus => l + r,
                     BinaryOp::Minus => l - r,
@@ -282,3 +412,47 @@ impl ExprTree {
         }
     }
 }
+
+/// What running a line of input comes to
+#[derive(Debug, PartialEq)]
+pub enum Outcome {
+    /// the line was worked out to a value
+    Value(Data),
+    /// a function was defined, with these parameters
+    Defined(String, Vec<String>),
+    /// the line was worked out as far as it would go, like a derivative of `sin x`
+    Expression(ExprTree),
+    /// an equation was solved for this variable, with these solutions
+    Solved(String, Vec<solve::Solution>),
+}
+
+impl Statement {
+    /// Runs a line of input, which may change the environment
+    pub fn execute(self, env: &mut Environment) -> Result<Outcome, Error> {
+        match self {
+            Statement::Expr(tree) => tree.eval(env).map(Outcome::Value),
+            Statement::Assign(name, tree) => {
+                let value = tree.eval(env)?;
+                env.set(name, value.clone());
+                Ok(Outcome::Value(value))
+            }
+            Statement::Define(name, params, body) => {
+                env.define(
+                    name.clone(),
+                    UserFunction {
+                        params: params.clone(),
+                        body,
+                    },
+                );
+                Ok(Outcome::Defined(name, params))
+            }
+            Statement::Differentiate(tree, var) => tree.derivative(&var, env).map(|t| match t {
+                ExprTree::Val(value) => Outcome::Value(value),
+                t => Outcome::Expression(t),
+            }),
+            Statement::Solve(lhs, rhs, var) => {
+                solve::solve(lhs, rhs, var, env).map(|(var, roots)| Outcome::Solved(var, roots))
+            }
+        }
+    }
+}
@@ -0,0 +1,167 @@
+/*! Promotion of `Int`s and `Rational`s to arbitrary-precision numbers when they overflow,
+and demotion back down again once they fit */
+
+use super::Data;
+use num::bigint::BigInt;
+use num::rational::{BigRational, Ratio};
+use num::traits::{One, ToPrimitive};
+
+impl From<BigInt> for Data {
+    fn from(n: BigInt) -> Self {
+        match n.to_i64() {
+            Some(small) => Self::Int(small),
+            None => Self::BigInt(n),
+        }
+    }
+}
+
+impl From<BigRational> for Data {
+    fn from(r: BigRational) -> Self {
+        if r.denom().is_one() {
+            return Self::from(r.numer().clone());
+        }
+        match (r.numer().to_i64(), r.denom().to_i64()) {
+            (Some(numer), Some(denom)) => Self::Rational(Ratio::new_raw(numer, denom)),
+            _ => Self::BigRational(r),
+        }
+    }
+}
+
+impl Data {
+    /// Is this one of the arbitrary-precision variants, factorisations count because they can be any size
+    pub fn is_big(&self) -> bool {
+        matches!(self, Self::BigInt(_) | Self::BigRational(_) | Self::Factorisation(_))
+    }
+
+    /// A factorisation is only for showing, anything done with it is done with the number itself
+    pub fn unfactorise(self) -> Self {
+        match self {
+            Self::Factorisation(f) => Self::from(f.value()),
+            other => other,
+        }
+    }
+
+    /// Views `Int`s, `Rational`s and their big counterparts as a `BigRational`, anything else is `None`
+    pub fn to_big_rational(&self) -> Option<BigRational> {
+        Some(match self {
+            Self::Int(n) => BigRational::from_integer((*n).into()),
+            Self::Rational(r) => BigRational::new_raw((*r.numer()).into(), (*r.denom()).into()),
+            Self::BigInt(n) => BigRational::from_integer(n.clone()),
+            Self::BigRational(r) => r.clone(),
+            Self::Factorisation(f) => BigRational::from_integer(f.value()),
+            _ => return None,
+        })
+    }
+
+    /// `Rational`s that are whole numbers should be `Int`s
+    pub fn from_ratio(r: Ratio<i64>) -> Self {
+        if *r.denom() == 1 {
+            Self::Int(*r.numer())
+        } else {
+            Self::Rational(r)
+        }
+    }
+}
+
+/// Does an operation on two exact rational numbers, first trying `small` in `i64`s,
+/// and if that overflows (or either side is already big) doing `big` in arbitrary precision instead.
+/// Returns `None` if either side isn't an exact rational, so the caller can deal with it.
+pub fn exact_arithmetic(
+    lhs: &Data,
+    rhs: &Data,
+    small: impl FnOnce(&Ratio<i64>, &Ratio<i64>) -> Option<Ratio<i64>>,
+    big: impl FnOnce(BigRational, BigRational) -> BigRational,
+) -> Option<Data> {
+    let as_small = |d: &Data| match d {
+        Data::Int(n) => Some(Ratio::from(*n)),
+        Data::Rational(r) => Some(*r),
+        _ => None,
+    };
+    if let (Some(l), Some(r)) = (as_small(lhs), as_small(rhs)) {
+        if let Some(result) = small(&l, &r) {
+            return Some(Data::from_ratio(result));
+        }
+    }
+    match (lhs.to_big_rational(), rhs.to_big_rational()) {
+        (Some(l), Some(r)) => Some(Data::from(big(l, r))),
+        _ => None,
+    }
+}
+
+/// A `Ratio<i64>` as a `BigRational`, for mixing the two
+pub fn big(r: Ratio<i64>) -> BigRational {
+    BigRational::new_raw((*r.numer()).into(), (*r.denom()).into())
+}
+
+pub fn big_rational_as_float(r: &BigRational) -> f64 {
+    r.to_f64().unwrap_or(f64::NAN)
+}
+
+#[cfg(test)]
+mod test {
+    use crate::error::Error;
+    use crate::eval::{complex::Complex, op::pow::Pow, Data};
+    use num::bigint::BigInt;
+
+    #[test]
+    fn promotes_on_overflow() {
+        let big = Data::Int(2).pow(Data::Int(70)).unwrap();
+        assert_eq!(big, Data::BigInt(BigInt::from(2).pow(70)));
+        assert_eq!(
+            (Data::Int(i64::MAX) + Data::Int(1)).unwrap(),
+            Data::BigInt(BigInt::from(i64::MAX) + 1)
+        );
+    }
+
+    #[test]
+    fn demotes_when_it_fits() {
+        let big = Data::Int(2).pow(Data::Int(70)).unwrap();
+        assert_eq!((big.clone() / Data::Int(2).pow(Data::Int(68)).unwrap()).unwrap(), Data::Int(4));
+        assert_eq!((big.clone() - big).unwrap(), Data::Int(0));
+    }
+
+    #[test]
+    fn powers_too_big_to_work_out_are_errors() {
+        assert!(matches!(Data::Int(2).pow(Data::Int(100_000_000)), Err(Error::Overflow(_))));
+        assert_eq!(Data::Int(1).pow(Data::Int(100_000_000)).unwrap(), Data::Int(1));
+        assert_eq!(Data::Int(-1).pow(Data::Int(100_000_001)).unwrap(), Data::Int(-1));
+        assert!(Data::Int(2).pow(Data::Int(100_000)).unwrap().is_big());
+    }
+
+    #[test]
+    fn exponents_past_an_i64() {
+        let huge = Data::Int(2).pow(Data::Int(70)).unwrap();
+        let huge_odd = (huge.clone() + Data::Int(1)).unwrap();
+        assert_eq!(Data::Int(-1).pow(huge_odd.clone()).unwrap(), Data::Int(-1));
+        assert_eq!(Data::Int(0).pow(huge.clone()).unwrap(), Data::Int(0));
+        assert_eq!(Data::Int(1).pow(huge.clone()).unwrap(), Data::Int(1));
+        let i = Data::from(Complex::i());
+        assert_eq!(i.clone().pow(huge.clone()).unwrap(), Data::Int(1));
+        assert_eq!(i.clone().pow(huge_odd).unwrap(), i);
+        assert!(matches!(Data::Int(2).pow(huge), Err(Error::Overflow(_))));
+    }
+
+    #[test]
+    fn powers_of_symbols() {
+        let pi = || Data::Symbol("pi".into());
+        assert_eq!(pi().pow(Data::Int(0)).unwrap(), Data::Int(1));
+        let past_a_u32 = Data::Int((1 << 32) + 2);
+        assert!(matches!(pi().pow(past_a_u32), Err(Error::Overflow(_))));
+    }
+
+    #[test]
+    fn factorial_sized_products() {
+        let product = (1..=30)
+            .map(Data::Int)
+            .fold(Data::Int(1), |acc, n| (acc * n).unwrap());
+        assert_eq!(
+            format!("{}", product),
+            "265252859812191058636308480000000"
+        );
+        let fraction = (Data::Int(1) / product).unwrap();
+        assert_eq!(
+            format!("{}", fraction),
+            "1/265252859812191058636308480000000"
+        );
+    }
+}
@@ -0,0 +1,168 @@
+/*! Complex numbers, `re + im i`, with both parts kept exact wherever they can be */
+
+use crate::error::Error;
+use super::{op::root::NthRoot, Data};
+use serde::{Deserialize, Serialize};
+use std::convert::TryFrom;
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct Complex {
+    pub re: Data,
+    pub im: Data,
+}
+
+impl Complex {
+    pub fn new(re: Data, im: Data) -> Self {
+        Complex { re, im }
+    }
+
+    /// the imaginary unit, the square root of -1
+    pub fn i() -> Self {
+        Complex::new(Data::Int(0), Data::Int(1))
+    }
+
+    /// `r(cos θ + i sin θ)`, which can't be kept exact
+    pub fn from_polar(r: f64, theta: f64) -> Self {
+        Complex::new(Data::Float(r * theta.cos()), Data::Float(r * theta.sin()))
+    }
+
+    /// the size and the angle from the positive real axis
+    pub fn to_polar(&self) -> Result<(f64, f64), Error> {
+        let (re, im) = (f64::try_from(self.re.clone())?, f64::try_from(self.im.clone())?);
+        Ok((re.hypot(im), im.atan2(re)))
+    }
+
+    pub fn add(self, rhs: Self) -> Result<Self, Error> {
+        Ok(Complex::new((self.re + rhs.re)?, (self.im + rhs.im)?))
+    }
+
+    /// (a + bi)(c + di) = (ac - bd) + (ad + bc)i
+    pub fn mul(self, rhs: Self) -> Result<Self, Error> {
+        let (a, b, c, d) = (self.re, self.im, rhs.re, rhs.im);
+        Ok(Complex::new(
+            (times(a.clone(), c.clone())? - times(b.clone(), d.clone())?)?,
+            (times(a, d)? + times(b, c)?)?,
+        ))
+    }
+
+    /// (a + bi)/(c + di) = (a + bi)(c - di)/(c^2 + d^2), which keeps the division real
+    pub fn div(self, rhs: Self) -> Result<Self, Error> {
+        let size = ((rhs.re.clone() * rhs.re.clone())? + (rhs.im.clone() * rhs.im.clone())?)?;
+        if size == Data::Int(0) {
+            return Err(Error::domain("Divide by 0"));
+        }
+        let numerator = self.mul(rhs.conjugate())?;
+        Ok(Complex::new(
+            (numerator.re / size.clone())?,
+            (numerator.im / size)?,
+        ))
+    }
+
+    pub fn neg(self) -> Self {
+        Complex::new(-self.re, -self.im)
+    }
+
+    pub fn conjugate(self) -> Self {
+        Complex::new(self.re, -self.im)
+    }
+
+    /// whole powers, by repeated squaring so they stay exact
+    pub fn pow(self, power: u32) -> Result<Self, Error> {
+        let mut result = Complex::new(Data::Int(1), Data::Int(0));
+        let mut square = self;
+        let mut power = power;
+        while power > 0 {
+            if power % 2 == 1 {
+                result = result.mul(square.clone())?;
+            }
+            power /= 2;
+            if power > 0 {
+                square = square.clone().mul(square)?;
+            }
+        }
+        Ok(result)
+    }
+
+    /// the square root with a positive real part, `√((|z| + a)/2) + i sgn(b) √((|z| - a)/2)` for `z = a + bi`,
+    /// which is exact whenever the parts are
+    pub fn sqrt(self) -> Result<Self, Error> {
+        let (a, b) = (self.re, self.im);
+        let size = (times(a.clone(), a.clone())? + times(b.clone(), b.clone())?)?.nth_root(2)?;
+        let re = ((size.clone() + a.clone())? / Data::Int(2))?.nth_root(2)?;
+        let im = ((size - a)? / Data::Int(2))?.nth_root(2)?;
+        Ok(Complex::new(re, if b < Data::Int(0) { -im } else { im }))
+    }
+
+    /// real powers, through the polar form
+    pub fn powf(self, power: f64) -> Result<Self, Error> {
+        let (r, theta) = self.to_polar()?;
+        Ok(Complex::from_polar(r.powf(power), theta * power))
+    }
+}
+
+/// multiplication, but 0 times a radical or a float is still exactly 0,
+/// which matters a lot here because one of the parts is 0 so often
+fn times(a: Data, b: Data) -> Result<Data, Error> {
+    if a == Data::Int(0) || b == Data::Int(0) {
+        Ok(Data::Int(0))
+    } else {
+        a * b
+    }
+}
+
+impl From<Data> for Complex {
+    fn from(data: Data) -> Self {
+        match data {
+            Data::Complex(z) => *z,
+            real => Complex::new(real, Data::Int(0)),
+        }
+    }
+}
+
+impl From<Complex> for Data {
+    /// complex numbers with nothing imaginary left in them are just real
+    fn from(z: Complex) -> Self {
+        match z.im {
+            Data::Int(0) => z.re,
+            Data::Float(0.) => z.re,
+            _ => Data::Complex(Box::new(z)),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::eval::op::root::NthRoot;
+
+    fn i() -> Data {
+        Complex::i().into()
+    }
+
+    #[test]
+    fn even_roots_of_negatives() {
+        assert_eq!(Data::Int(-4).nth_root(2).unwrap(), (Data::Int(2) * i()).unwrap());
+        assert_eq!(format!("{}", Data::Int(-9).nth_root(2).unwrap()), "3i");
+        assert_eq!(format!("{}", Data::Int(-16).nth_root(4).unwrap()), "√2 + i√2");
+        assert_eq!(Data::Int(-4).nth_root(-2).unwrap(), (i() / Data::Int(-2)).unwrap());
+        assert_eq!(format!("{}", Data::Int(-64).nth_root(6).unwrap()), "√3 + i");
+    }
+
+    #[test]
+    fn square_roots_are_exact() {
+        let two_i = (Data::Int(2) * i()).unwrap();
+        assert_eq!(two_i.nth_root(2).unwrap(), (Data::Int(1) + i()).unwrap());
+        let root_root = Data::Int(-4).nth_root(2).unwrap().nth_root(2).unwrap();
+        assert_eq!(root_root, (Data::Int(1) + i()).unwrap());
+        let z = (Data::Int(3) + (Data::Int(-4) * i()).unwrap()).unwrap();
+        assert_eq!(z.nth_root(2).unwrap(), (Data::Int(2) - i()).unwrap());
+    }
+
+    #[test]
+    fn stays_exact() {
+        let z = (Data::Int(1) + i()).unwrap();
+        assert_eq!((z.clone() * z.clone()).unwrap(), (Data::Int(2) * i()).unwrap());
+        assert_eq!((i() * i()).unwrap(), Data::Int(-1));
+        assert_eq!(format!("{}", (Data::Int(1) / z).unwrap()), "1/2 - (1/2)i");
+    }
+}
@@ -0,0 +1,314 @@
+/*! Symbolic differentiation, which works on the tree rather than on `Data`,
+because most derivatives (like that of `sin x`) can't be worked out to a value */
+
+use crate::error::Error;
+use super::{
+    env::{UserFunction, MAX_CALL_DEPTH},
+    op::calculate_fn::{is_function, partial_derivatives},
+    Data, Environment,
+};
+use crate::parser::{BinaryOp, ExprTree, UnaryOp};
+use std::collections::HashMap;
+
+// these are just to stop the derivative rules from drowning in `Box`es
+pub fn val(n: i64) -> ExprTree {
+    ExprTree::Val(Data::Int(n))
+}
+
+pub fn neg(a: ExprTree) -> ExprTree {
+    ExprTree::UNode(UnaryOp::Neg, a.into())
+}
+
+pub fn binary(op: BinaryOp, a: ExprTree, b: ExprTree) -> ExprTree {
+    ExprTree::BNode(op, a.into(), b.into())
+}
+
+pub fn add(a: ExprTree, b: ExprTree) -> ExprTree {
+    binary(BinaryOp::Plus, a, b)
+}
+
+pub fn sub(a: ExprTree, b: ExprTree) -> ExprTree {
+    binary(BinaryOp::Minus, a, b)
+}
+
+pub fn mul(a: ExprTree, b: ExprTree) -> ExprTree {
+    binary(BinaryOp::Mul, a, b)
+}
+
+pub fn div(a: ExprTree, b: ExprTree) -> ExprTree {
+    binary(BinaryOp::Div, a, b)
+}
+
+pub fn pow(a: ExprTree, b: ExprTree) -> ExprTree {
+    binary(BinaryOp::Exp, a, b)
+}
+
+pub fn call(name: &str, args: Vec<ExprTree>) -> ExprTree {
+    ExprTree::Call(name.to_string(), args)
+}
+
+impl ExprTree {
+    /// The derivative of the tree with respect to `var`, simplified as far as it'll go.
+    /// Any other free variables are held constant
+    pub fn derivative(self, var: &str, env: &Environment) -> Result<ExprTree, Error> {
+        let expanded = self.expand(var, env, 0)?;
+        let derivative = expanded.differentiate(var, env)?;
+        // variables and user functions have all been expanded, so all that's needed is the angle mode
+        let mut bare = Environment::new();
+        bare.set_angle_mode(env.angle_mode());
+        Ok(derivative.simplify(&bare))
+    }
+
+    /// Swaps variables for their values and calls to user functions for their bodies,
+    /// leaving only `var` and the free variables behind
+    fn expand(self, var: &str, env: &Environment, depth: usize) -> Result<ExprTree, Error> {
+        Ok(match self {
+            ExprTree::Ident(name) if name != var => match env.get(&name) {
+                Some(value) => ExprTree::Val(value.clone()),
+                None => ExprTree::Ident(name),
+            },
+            ExprTree::Call(name, args) => {
+                let mut args = args
+                    .into_iter()
+                    .map(|arg| arg.expand(var, env, depth))
+                    .collect::<Result<Vec<_>, _>>()?;
+                match env.function(&name) {
+                    Some(function) => {
+                        if depth >= MAX_CALL_DEPTH {
+                            return Err(Error::Recursion {
+                                name,
+                                depth: MAX_CALL_DEPTH,
+                            });
+                        }
+                        inline(&name, function, args)?.expand(var, env, depth + 1)?
+                    }
+                    // a multiplication, just like when it's evaluated
+                    None if args.len() == 1 && !is_function(&name) => ExprTree::BNode(
+                        BinaryOp::Mul,
+                        ExprTree::Ident(name).expand(var, env, depth)?.into(),
+                        args.remove(0).into(),
+                    ),
+                    None => ExprTree::Call(name, args),
+                }
+            }
+            ExprTree::UNode(op, t) => ExprTree::UNode(op, t.expand(var, env, depth)?.into()),
+            ExprTree::BNode(op, lhs, rhs) => ExprTree::BNode(
+                op,
+                lhs.expand(var, env, depth)?.into(),
+                rhs.expand(var, env, depth)?.into(),
+            ),
+            leaf => leaf,
+        })
+    }
+
+    /// Does `var` show up anywhere in the tree
+    fn depends_on(&self, var: &str) -> bool {
+        match self {
+            ExprTree::Val(Data::Polynomial(p)) => p.variables().iter().any(|v| *v == var),
+            ExprTree::Val(_) => false,
+            ExprTree::Ident(name) => name == var,
+            ExprTree::Call(_, args) => args.iter().any(|arg| arg.depends_on(var)),
+            ExprTree::UNode(_, t) => t.depends_on(var),
+            ExprTree::BNode(_, lhs, rhs) => lhs.depends_on(var) || rhs.depends_on(var),
+        }
+    }
+
+    /// The rules of differentiation, this makes a lot of silly looking trees like `1 * x + x * 0`,
+    /// which `simplify` cleans up afterwards
+    fn differentiate(&self, var: &str, env: &Environment) -> Result<ExprTree, Error> {
+        if !self.depends_on(var) {
+            return Ok(val(0));
+        }
+        Ok(match self {
+            ExprTree::Val(Data::Polynomial(p)) => ExprTree::Val(p.derivative(var)?.into()),
+            ExprTree::Val(_) => val(0),
+            ExprTree::Ident(_) => val(1), // it depends on var, so it is var
+            // the chain rule, through every argument
+            ExprTree::Call(name, args) => {
+                let partials = partial_derivatives(name, args, env.angle_mode())?;
+                let mut result = val(0);
+                for (partial, arg) in partials.into_iter().zip(args) {
+                    if arg.depends_on(var) {
+                        result = add(result, mul(partial, arg.differentiate(var, env)?));
+                    }
+                }
+                result
+            }
+            ExprTree::UNode(UnaryOp::Neg, t) => neg(t.differentiate(var, env)?),
+            ExprTree::UNode(UnaryOp::Percent, t) => div(t.differentiate(var, env)?, val(100)),
+            // that would need the digamma function
+            ExprTree::UNode(_, _) => {
+                return Err(Error::algebra(format!("can't differentiate {}", self)))
+            }
+            ExprTree::BNode(op, u, v) => {
+                let (u, v) = (u.as_ref().clone(), v.as_ref().clone());
+                let (du, dv) = (u.differentiate(var, env)?, v.differentiate(var, env)?);
+                match op {
+                    BinaryOp::Plus => add(du, dv),
+                    BinaryOp::Minus => sub(du, dv),
+                    BinaryOp::Mul => add(mul(du, v), mul(u, dv)),
+                    BinaryOp::Div => div(sub(mul(du, v.clone()), mul(u, dv)), pow(v, val(2))),
+                    // the power rule
+                    BinaryOp::Exp if !v.depends_on(var) => {
+                        mul(mul(v.clone(), pow(u, sub(v, val(1)))), du)
+                    }
+                    // exponentials
+                    BinaryOp::Exp if !u.depends_on(var) => {
+                        mul(mul(pow(u.clone(), v), call("ln", vec![u])), dv)
+                    }
+                    // u^v = e^(v ln u), so the derivative is u^v (v' ln u + v u' / u)
+                    BinaryOp::Exp => mul(
+                        pow(u.clone(), v.clone()),
+                        add(
+                            mul(dv, call("ln", vec![u.clone()])),
+                            div(mul(v, du), u),
+                        ),
+                    ),
+                }
+            }
+        })
+    }
+
+    /// Works out every part of the tree that can be worked out, and gets rid of adding 0s,
+    /// multiplying by 1s and the like
+    pub fn simplify(self, env: &Environment) -> ExprTree {
+        let tree = match self {
+            ExprTree::Call(name, args) => {
+                ExprTree::Call(name, args.into_iter().map(|a| a.simplify(env)).collect())
+            }
+            ExprTree::UNode(op, t) => ExprTree::UNode(op, t.simplify(env).into()),
+            ExprTree::BNode(op, lhs, rhs) => {
+                ExprTree::BNode(op, lhs.simplify(env).into(), rhs.simplify(env).into())
+            }
+            leaf => leaf,
+        };
+        if let Ok(value) = tree.clone().eval(env) {
+            return ExprTree::Val(value);
+        }
+        let is = |t: &ExprTree, n: i64| *t == val(n);
+        match tree {
+            ExprTree::UNode(UnaryOp::Neg, t) => match *t {
+                ExprTree::UNode(UnaryOp::Neg, inner) => *inner,
+                t => neg(t),
+            },
+            ExprTree::BNode(op, lhs, rhs) => match op {
+                BinaryOp::Plus if is(&lhs, 0) => *rhs,
+                BinaryOp::Plus | BinaryOp::Minus if is(&rhs, 0) => *lhs,
+                BinaryOp::Minus if is(&lhs, 0) => neg(*rhs).simplify(env),
+                BinaryOp::Mul if is(&lhs, 0) || is(&rhs, 0) => val(0),
+                BinaryOp::Mul if is(&lhs, 1) => *rhs,
+                BinaryOp::Mul if is(&rhs, 1) => *lhs,
+                // minus signs get pulled out to the front, so they can cancel
+                BinaryOp::Mul | BinaryOp::Div if is_negated(&lhs) => {
+                    neg(binary(op, negate(*lhs), *rhs)).simplify(env)
+                }
+                BinaryOp::Mul | BinaryOp::Div if is_negated(&rhs) => {
+                    neg(binary(op, *lhs, negate(*rhs))).simplify(env)
+                }
+                BinaryOp::Mul => match (*lhs, *rhs) {
+                    // `a * 1/b` is just `a / b`
+                    (a, ExprTree::BNode(BinaryOp::Div, one, b))
+                    | (ExprTree::BNode(BinaryOp::Div, one, b), a)
+                        if is(&one, 1) =>
+                    {
+                        div(a, *b)
+                    }
+                    // numbers go at the front, `2 * cos(x)` rather than `cos(x) * 2`
+                    (a, b @ ExprTree::Val(_)) if !matches!(a, ExprTree::Val(_)) => mul(b, a),
+                    (a, b) => mul(a, b),
+                },
+                BinaryOp::Div if is(&lhs, 0) => val(0),
+                BinaryOp::Div if is(&rhs, 1) => *lhs,
+                BinaryOp::Exp if is(&rhs, 0) => val(1),
+                BinaryOp::Exp if is(&rhs, 1) => *lhs,
+                op => binary(op, *lhs, *rhs),
+            },
+            other => other,
+        }
+    }
+}
+
+/// Is this a negative number, or something with a minus sign in front of it
+fn is_negated(tree: &ExprTree) -> bool {
+    match tree {
+        ExprTree::Val(d) => d < &Data::Int(0),
+        ExprTree::UNode(UnaryOp::Neg, _) => true,
+        _ => false,
+    }
+}
+
+/// takes the minus sign off of something `is_negated`
+fn negate(tree: ExprTree) -> ExprTree {
+    match tree {
+        ExprTree::Val(d) => ExprTree::Val(-d),
+        ExprTree::UNode(UnaryOp::Neg, t) => *t,
+        t => neg(t),
+    }
+}
+
+/// The body of a user function, with the arguments swapped in for its parameters
+fn inline(name: &str, function: &UserFunction, args: Vec<ExprTree>) -> Result<ExprTree, Error> {
+    function.check_arity(name, args.len())?;
+    let bindings: HashMap<_, _> = function.params.iter().cloned().zip(args).collect();
+    Ok(function.body.clone().substitute(&bindings))
+}
+
+impl ExprTree {
+    fn substitute(self, bindings: &HashMap<String, ExprTree>) -> ExprTree {
+        match self {
+            ExprTree::Ident(name) => match bindings.get(&name) {
+                Some(tree) => tree.clone(),
+                None => ExprTree::Ident(name),
+            },
+            ExprTree::Call(name, args) => ExprTree::Call(
+                name,
+                args.into_iter().map(|a| a.substitute(bindings)).collect(),
+            ),
+            ExprTree::UNode(op, t) => ExprTree::UNode(op, t.substitute(bindings).into()),
+            ExprTree::BNode(op, lhs, rhs) => ExprTree::BNode(
+                op,
+                lhs.substitute(bindings).into(),
+                rhs.substitute(bindings).into(),
+            ),
+            leaf => leaf,
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::eval::Environment;
+    use crate::parser::parse_string;
+
+    fn d(input: &str) -> String {
+        let env = Environment::new();
+        format!(
+            "{}",
+            parse_string(input).unwrap().derivative("x", &env).unwrap()
+        )
+    }
+
+    #[test]
+    fn polynomials() {
+        assert_eq!(d("x^3 + 2*x"), "3x^2 + 2");
+        assert_eq!(d("(x + 1)^2"), "2x + 2");
+        assert_eq!(d("y * x^2"), "2xy");
+        assert_eq!(d("x(x + 1)"), "2x + 1");
+    }
+
+    #[test]
+    fn chain_rule() {
+        assert_eq!(d("sin(x^2)"), "2x * cos(x^2)");
+        assert_eq!(d("ln(x)"), "1 / x");
+        assert_eq!(d("cos x"), "-sin(x)");
+    }
+
+    #[test]
+    fn through_user_functions() {
+        let mut env = Environment::new();
+        let define = crate::parser::parse_statement("f(t) = t^2 + t").unwrap();
+        define.execute(&mut env).unwrap();
+        let tree = parse_string("f(x^2)").unwrap();
+        assert_eq!(format!("{}", tree.derivative("x", &env).unwrap()), "4x^3 + 2x");
+    }
+}
@@ -0,0 +1,278 @@
+/*! The environment that expressions are evaluated in, which carries values between lines of a session */
+
+use crate::error::Error;
+use super::{AngleMode, Data, Outcome, Polynomial};
+use crate::parser::ExprTree;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::convert::TryFrom;
+
+/// How many user function calls deep an evaluation can go before we give up on it
+pub const MAX_CALL_DEPTH: usize = 128;
+
+/// A function the user has defined, like `f(x, y) = x^2 + y`
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct UserFunction {
+    pub params: Vec<String>,
+    pub body: ExprTree,
+}
+
+impl UserFunction {
+    pub fn check_arity(&self, name: &str, given: usize) -> Result<(), Error> {
+        if given == self.params.len() {
+            return Ok(());
+        }
+        Err(Error::Arity {
+            name: name.to_string(),
+            expected: self.params.len().to_string(),
+            given,
+        })
+    }
+}
+
+/// How decimals like `0.1` are read, as the exact fraction they're written as or as floats
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub enum DecimalMode {
+    #[default]
+    Exact,
+    Float,
+}
+
+impl std::str::FromStr for DecimalMode {
+    type Err = Error;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Ok(match s {
+            "exact" => Self::Exact,
+            "float" => Self::Float,
+            unknown => {
+                return Err(Error::invalid_argument(format!(
+                    "there's no decimal mode called {}, the modes are exact and float",
+                    unknown
+                )))
+            }
+        })
+    }
+}
+
+impl std::fmt::Display for DecimalMode {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{}",
+            match self {
+                Self::Exact => "exact fractions",
+                Self::Float => "floats",
+            }
+        )
+    }
+}
+
+/// The part of an environment that `!save` writes to a file and `!load` reads back in
+#[derive(Default, Serialize, Deserialize)]
+pub struct Session {
+    variables: HashMap<String, Data>,
+    functions: HashMap<String, UserFunction>,
+    history: Vec<(String, Data)>,
+}
+
+/// Everything the user has defined so far
+#[derive(Clone, Debug, Default)]
+pub struct Environment {
+    variables: HashMap<String, Data>,
+    functions: HashMap<String, UserFunction>,
+    angle_mode: AngleMode,
+    decimal_mode: DecimalMode,
+    /// every line that came out to a value, with that value, oldest first
+    history: Vec<(String, Data)>,
+}
+
+impl Environment {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// looks up the value of a variable, `ans` and `$n` are earlier results unless they've been given other values
+    pub fn get(&self, name: &str) -> Option<&Data> {
+        self.variables.get(name).or_else(|| self.recall(name))
+    }
+
+    /// `ans` is the last result, `$3` is the third one and `$-2` is the one before last
+    fn recall(&self, name: &str) -> Option<&Data> {
+        let n: i64 = match name {
+            "ans" => -1,
+            name => name.strip_prefix('$')?.parse().ok()?,
+        };
+        let i = match n {
+            n if n > 0 => n - 1,
+            n if n < 0 => self.history.len() as i64 + n,
+            _ => return None,
+        };
+        self.history.get(usize::try_from(i).ok()?).map(|(_, value)| value)
+    }
+
+    /// keeps the outcome of `input` in the history, if it came out to a value
+    pub fn remember(&mut self, input: &str, outcome: &Outcome) {
+        if let Outcome::Value(value) = outcome {
+            self.history.push((input.to_string(), value.clone()));
+        }
+    }
+
+    /// what's been defined so far and the history, for saving
+    pub fn session(&self) -> Session {
+        Session {
+            variables: self.variables.clone(),
+            functions: self.functions.clone(),
+            history: self.history.clone(),
+        }
+    }
+
+    /// goes back to a saved session, forgetting whatever was defined before, but keeping the modes
+    pub fn restore(&mut self, session: Session) {
+        self.variables = session.variables;
+        self.functions = session.functions;
+        self.history = session.history;
+    }
+
+    /// the lines that came out to values and what they came out to, `$1` first
+    pub fn history(&self) -> &[(String, Data)] {
+        &self.history
+    }
+
+    /// binds a value to a variable name, overwriting anything already there
+    pub fn set(&mut self, name: String, value: Data) {
+        self.variables.insert(name, value);
+    }
+
+    /// every variable, sorted by name
+    pub fn variables(&self) -> Vec<(&String, &Data)> {
+        let mut variables: Vec<_> = self.variables.iter().collect();
+        variables.sort_by_key(|(name, _)| *name);
+        variables
+    }
+
+    /// every user-defined function, sorted by name
+    pub fn functions(&self) -> Vec<(&String, &UserFunction)> {
+        let mut functions: Vec<_> = self.functions.iter().collect();
+        functions.sort_by_key(|(name, _)| *name);
+        functions
+    }
+
+    /// forgets every variable, function and earlier result, but keeps the modes
+    pub fn clear(&mut self) {
+        self.restore(Session::default());
+    }
+
+    /// looks up a user-defined function
+    pub fn function(&self, name: &str) -> Option<&UserFunction> {
+        self.functions.get(name)
+    }
+
+    /// defines a function, overwriting any earlier definition with the same name
+    pub fn define(&mut self, name: String, function: UserFunction) {
+        self.functions.insert(name, function);
+    }
+
+    /// the unit trig functions take and give angles in
+    pub fn angle_mode(&self) -> AngleMode {
+        self.angle_mode
+    }
+
+    pub fn set_angle_mode(&mut self, mode: AngleMode) {
+        self.angle_mode = mode;
+    }
+
+    /// whether decimals typed in are exact or floats
+    pub fn decimal_mode(&self) -> DecimalMode {
+        self.decimal_mode
+    }
+
+    pub fn set_decimal_mode(&mut self, mode: DecimalMode) {
+        self.decimal_mode = mode;
+    }
+}
+
+/// What an expression can see while it's being evaluated: the environment,
+/// plus the arguments of whichever user function we're inside of
+pub struct Scope<'e> {
+    pub env: &'e Environment,
+    locals: HashMap<String, Data>,
+    depth: usize,
+}
+
+impl<'e> Scope<'e> {
+    /// the scope of a line typed in at the top level
+    pub fn global(env: &'e Environment) -> Self {
+        Scope {
+            env,
+            locals: HashMap::new(),
+            depth: 0,
+        }
+    }
+
+    /// the scope of an equation being solved for `var`, which stays a variable even if it's been given a value
+    pub fn solving(env: &'e Environment, var: &str) -> Self {
+        let mut locals = HashMap::new();
+        locals.insert(var.to_string(), Data::Polynomial(Polynomial::variable(var.to_string())));
+        Scope { env, locals, depth: 0 }
+    }
+
+    /// arguments shadow variables of the same name
+    pub fn get(&self, name: &str) -> Option<&Data> {
+        self.locals.get(name).or_else(|| self.env.get(name))
+    }
+
+    /// the scope inside of a call to `function`, with `args` bound to its parameters
+    pub fn enter(&self, name: &str, function: &UserFunction, args: Vec<Data>) -> Result<Self, Error> {
+        function.check_arity(name, args.len())?;
+        if self.depth >= MAX_CALL_DEPTH {
+            return Err(Error::Recursion {
+                name: name.to_string(),
+                depth: MAX_CALL_DEPTH,
+            });
+        }
+        Ok(Scope {
+            env: self.env,
+            locals: function.params.iter().cloned().zip(args).collect(),
+            depth: self.depth + 1,
+        })
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::eval::op::root::NthRoot;
+    use crate::parser::parse_statement;
+
+    fn run(env: &mut Environment, line: &str) -> Outcome {
+        let outcome = parse_statement(line).and_then(|s| s.execute(env)).unwrap();
+        env.remember(line, &outcome);
+        outcome
+    }
+
+    #[test]
+    fn earlier_results_can_be_used() {
+        let mut env = Environment::new();
+        run(&mut env, "2 + 3");
+        run(&mut env, "f(x) = x + 1");
+        run(&mut env, "sqrt 8");
+        // defining a function doesn't come out to a value, so it isn't in the history
+        let ten_root_two = (Data::Int(10) * Data::Int(2).nth_root(2).unwrap()).unwrap();
+        assert_eq!(run(&mut env, "ans * $1"), Outcome::Value(ten_root_two));
+        assert_eq!(run(&mut env, "$-3"), Outcome::Value(Data::Int(5)));
+        assert!(parse_statement("$7").and_then(|s| s.execute(&mut env)).is_err());
+    }
+
+    #[test]
+    fn sessions_survive_being_saved() {
+        let mut env = Environment::new();
+        run(&mut env, "let a = sqrt 8 + x^2 y");
+        run(&mut env, "f(x) = x^2 + a");
+        let saved = ron::ser::to_string(&env.session()).unwrap();
+        let mut loaded = Environment::new();
+        loaded.restore(ron::de::from_str(&saved).unwrap());
+        assert_eq!(loaded.get("a"), env.get("a"));
+        assert_eq!(loaded.function("f"), env.function("f"));
+        assert_eq!(loaded.history(), env.history());
+    }
+}
@@ -0,0 +1,170 @@
+/*! Logarithms that don't come out exactly, like `log(2)`, kept as they are rather than turned into floats.
+The log rules let them combine, `log(2) + log(5) = log(10) = 1` */
+
+use crate::error::Error;
+use super::{op::pow::Pow, primes::factorise_big, Data};
+use num::bigint::BigInt;
+use num::integer::Integer;
+use num::rational::BigRational;
+use num::traits::{One, Signed};
+use serde::{Deserialize, Serialize};
+use std::convert::TryFrom;
+
+/// `k log(x) = log(x^k)`, but past this x^k would be an unreadable number
+const BIGGEST_POWER_RULE: i64 = 64;
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct Logarithm {
+    /// always positive, and never a rational power of the base, or it would have come out exactly
+    pub argument: Data,
+    /// always positive and never 1, `e` for natural logarithms
+    pub base: Data,
+}
+
+impl Logarithm {
+    /// `log_base(argument)`, exact if the argument is a rational power of the base and kept as a logarithm otherwise.
+    /// The argument and base need to have already been checked to be positive, and the base to not be 1
+    pub fn of(argument: Data, base: Data) -> Result<Data, Error> {
+        if argument == Data::Int(1) {
+            return Ok(Data::Int(0));
+        }
+        if argument == base {
+            return Ok(Data::Int(1));
+        }
+        // a = c^p and b = c^q means log_b(a) = p/q, and if b = 1/c it's -p/q
+        if let (Some((a, p)), Some((b, q))) = (rational_power(&argument), rational_power(&base)) {
+            if a == b {
+                return Ok(Data::from(p / q));
+            } else if a == b.recip() {
+                return Ok(Data::from(-p / q));
+            }
+        }
+        Ok(Data::Log(Box::new(Logarithm { argument, base })))
+    }
+
+    /// Can `log` be kept exactly, or should it just be a float
+    pub fn keeps(x: &Data) -> bool {
+        x.to_big_rational().is_some() || matches!(x, Data::Radical(_))
+    }
+
+    /// `log(x) + log(y) = log(xy)`, when the bases are the same
+    pub fn add(self, rhs: Self) -> Result<Data, Error> {
+        if self.base == rhs.base {
+            Logarithm::of((self.argument * rhs.argument)?, self.base)
+        } else {
+            Ok(Data::Float(self.as_float()? + rhs.as_float()?))
+        }
+    }
+
+    /// `k log(x) = log(x^k)`, for rational `k` that aren't too big
+    pub fn scale(self, k: Data) -> Result<Data, Error> {
+        match k.to_big_rational() {
+            Some(r) if r.numer().abs() <= BigInt::from(BIGGEST_POWER_RULE) => {
+                Logarithm::of(self.argument.pow(k)?, self.base)
+            }
+            _ => Ok(Data::Float(self.as_float()? * f64::try_from(k)?)),
+        }
+    }
+
+    /// `log_b(x) + k = log_b(x b^k)`, for rational `k` that aren't too big and rational bases
+    pub fn shift(self, k: Data) -> Result<Data, Error> {
+        if self.base.to_big_rational().is_some() {
+            if let Some(r) = k.to_big_rational() {
+                if r.numer().abs() <= BigInt::from(BIGGEST_POWER_RULE) {
+                    let argument = (self.argument.clone() * self.base.clone().pow(k.clone())?)?;
+                    if Logarithm::keeps(&argument) {
+                        return Logarithm::of(argument, self.base);
+                    }
+                }
+            }
+        }
+        Ok(Data::Float(self.as_float()? + f64::try_from(k)?))
+    }
+
+    /// `log_b(x) / log_b(y) = log_y(x)`, which is the change of base formula backwards
+    pub fn div(self, rhs: Self) -> Result<Data, Error> {
+        if self.base == rhs.base {
+            Logarithm::of(self.argument, rhs.argument)
+        } else {
+            Ok(Data::Float(self.as_float()? / rhs.as_float()?))
+        }
+    }
+
+    /// `-log(x) = log(1/x)`
+    pub fn neg(self) -> Self {
+        let argument = (Data::Int(1) / self.argument).unwrap(); // logarithms are never of 0
+        Logarithm { argument, ..self }
+    }
+
+    pub fn as_float(self) -> Result<f64, Error> {
+        Ok(f64::try_from(self.argument)?.ln() / f64::try_from(self.base)?.ln())
+    }
+}
+
+/// `x` as `c^p` with `c` as small as it can be, for rationals and for radicals of rationals
+fn rational_power(x: &Data) -> Option<(BigRational, BigRational)> {
+    match x {
+        Data::Radical(r) => {
+            // (a ⁿ√b)^n = a^n b
+            let whole = (Data::from(r.coefficient.clone()).pow(Data::from(r.index as i64)).ok()? * *r.radicand.clone()).ok()?;
+            let (c, p) = rational_power(&whole)?;
+            Some((c, p / BigInt::from(r.index)))
+        }
+        x => {
+            let r = x.to_big_rational()?;
+            let (c, p) = perfect_power(&r);
+            Some((c, BigRational::from_integer(p.into())))
+        }
+    }
+}
+
+/// `r` as `c^p` with `p` as big as it can be
+fn perfect_power(r: &BigRational) -> (BigRational, u32) {
+    let (top, bottom) = (factorise_big(r.numer()), factorise_big(r.denom()));
+    let p = top.iter().chain(&bottom).fold(0, |p, (_, k)| p.gcd(k));
+    if p <= 1 {
+        return (r.clone(), 1);
+    }
+    let root = |factors: Vec<(BigInt, u32)>| {
+        factors
+            .into_iter()
+            .map(|(q, k)| q.pow(k / p))
+            .fold(BigInt::one(), |a, b| a * b)
+    };
+    let sign = if r.is_negative() { -BigInt::one() } else { BigInt::one() };
+    (BigRational::new(sign * root(top), root(bottom)), p)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::eval::Environment;
+    use crate::parser::parse_string;
+
+    fn log(x: Data, base: i64) -> Data {
+        Logarithm::of(x, Data::Int(base)).unwrap()
+    }
+
+    #[test]
+    fn exact_when_its_a_power_of_the_base() {
+        assert_eq!(log(Data::Int(8), 2), Data::Int(3));
+        assert_eq!(log(Data::Int(2), 4), Data::Rational((1, 2).into()));
+        assert_eq!(log(Data::Rational((1, 1000).into()), 10), Data::Int(-3));
+        // 8/27 = (2/3)^3 and 9/4 = (2/3)^-2
+        let (x, base) = (Data::Rational((8, 27).into()), Data::Rational((9, 4).into()));
+        assert_eq!(Logarithm::of(x, base), Ok(Data::Rational((-3, 2).into())));
+        let root_two = Data::Int(2).pow(Data::Rational((1, 2).into())).unwrap();
+        assert_eq!(log(root_two, 2), Data::Rational((1, 2).into()));
+        assert!(matches!(log(Data::Int(20), 10), Data::Log(_)));
+    }
+
+    #[test]
+    fn written_with_the_base() {
+        let eval = |input: &str| parse_string(input).unwrap().eval(&Environment::new()).unwrap();
+        assert_eq!(eval("log_2 8"), Data::Int(3));
+        assert_eq!(eval("log_4 2"), Data::Rational((1, 2).into()));
+        // adding a rational keeps it exact, log 20 + 1 = log 200
+        assert_eq!(eval("log 20 + 1"), log(Data::Int(200), 10));
+        assert_eq!(eval("log_2 3 - 1"), log(Data::Rational((3, 2).into()), 2));
+    }
+}
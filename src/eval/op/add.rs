@@ -1,15 +1,41 @@
-use crate::eval::{op::pow::Pow, ratio_as_float, Data, DivisibleBy, OrMerge, Radical, Symbolic};
+use crate::error::Error;
+use crate::eval::{
+    big::exact_arithmetic, complex::Complex, radical_sum::RadicalSum, Data, OrMerge, Polynomial, Symbolic,
+};
+use num::traits::CheckedAdd;
 use std::convert::TryFrom;
 use std::ops::Add;
 
 impl Add for Data {
-    type Output = Result<Self, String>;
+    type Output = Result<Self, Error>;
     fn add(self, rhs: Self) -> Self::Output {
+        // a factorisation is just a number once there's arithmetic to do
+        if let (Self::Factorisation(_), _) | (_, Self::Factorisation(_)) = (&self, &rhs) {
+            return self.unfactorise() + rhs.unfactorise();
+        }
+        // ints and rationals, big or small, all get added the same way
+        if let Some(sum) = exact_arithmetic(&self, &rhs, |a, b| a.checked_add(b), |a, b| a + b) {
+            return Ok(sum);
+        }
+        // adding 0 shouldn't turn a radical into a float
+        if rhs == Self::Int(0) {
+            return Ok(self);
+        } else if self == Self::Int(0) {
+            return Ok(rhs);
+        }
+        // radicals and rationals added together stay exact, see `radical_sum`
+        if let Some((lhs, rhs)) = RadicalSum::both(&self, &rhs) {
+            return Ok(lhs.add(rhs)?.into());
+        }
         match (self, rhs) {
-            (Self::Int(lhs), Self::Int(rhs)) => Ok(Self::Int(lhs + rhs)),
-            (Self::Int(lhs), Self::Rational(rhs)) => Ok(Self::Rational(rhs + lhs)),
-            (Self::Rational(lhs), Self::Rational(rhs)) => Ok(Self::Rational(rhs + lhs)),
-            (Self::Rational(lhs), Self::Int(rhs)) => Ok(Self::Rational(lhs + rhs)),
+            (Self::Polynomial(p), a) | (a, Self::Polynomial(p)) => {
+                Ok(p.add(Polynomial::from(a))?.into())
+            }
+            (Self::Complex(z), a) | (a, Self::Complex(z)) => Ok(Complex::from(a).add(*z)?.into()),
+            // log(x) + log(y) = log(xy)
+            (Self::Log(a), Self::Log(b)) => a.add(*b),
+            // log(x) + k = log(x 10^k)
+            (Self::Log(l), k) | (k, Self::Log(l)) if k.to_big_rational().is_some() => l.shift(k),
             (Self::Float(lhs), a) => Ok(Self::Float(lhs + f64::try_from(a)?)),
             (a, Self::Float(rhs)) => Ok(Self::Float(f64::try_from(a)? + rhs)),
             (Self::Symbol(sym), a) => Ok(Self::Symbolic(Box::new(Symbolic {
@@ -72,55 +98,8 @@ impl Add for Data {
                     constant: constant.or_merge(|a, b| a + b, Ok(Some(a)))?,
                 })))
             }
-            (Self::Radical(rad), Self::Int(int)) | (Self::Int(int), Self::Radical(rad)) => {
-                // assuming the radical is not illformed (i.e. shouldn't exist), this shouldn't yield a pretty radical, therefore it must go to a float ;-;
-                Ok(Self::Float(rad.as_float()? + int as f64))
-            }
-            (Self::Radical(lhs), Self::Radical(rhs)) => {
-                if lhs.index == rhs.index && lhs.radicand == rhs.radicand {
-                    Ok(Self::Radical(Radical {
-                        coefficient: lhs.coefficient + rhs.coefficient,
-                        index: lhs.index,
-                        radicand: lhs.radicand,
-                    }))
-                }
-                // weird edge case: you can manipulate one to look like the other because the indices are divisible.
-                else if lhs.index.divisible_by(rhs.index)
-                    && lhs.radicand
-                        == rhs
-                            .radicand
-                            .clone()
-                            .pow(((lhs.index / rhs.index) as i64).into())?
-                            .into()
-                {
-                    Ok(Self::Radical(Radical {
-                        coefficient: lhs.coefficient + rhs.coefficient,
-                        index: lhs.index,
-                        radicand: lhs.radicand,
-                    }))
-                }
-                // needs to commute Bruh
-                else if rhs.index.divisible_by(lhs.index)
-                    && rhs.radicand
-                        == lhs
-                            .radicand
-                            .clone()
-                            .pow(((rhs.index / lhs.index) as i64).into())?
-                            .into()
-                {
-                    Ok(Self::Radical(Radical {
-                        coefficient: lhs.coefficient + rhs.coefficient,
-                        index: rhs.index,
-                        radicand: rhs.radicand,
-                    }))
-                } else {
-                    Ok(Self::Float(lhs.as_float()? + rhs.as_float()?))
-                }
-            }
-            (Self::Radical(rad), Self::Rational(rat))
-            | (Self::Rational(rat), Self::Radical(rad)) => {
-                Ok(Self::Float(rad.as_float()? + ratio_as_float(rat)))
-            }
+            // only big numbers with floaty things are left
+            (a, b) => Ok(Self::Float(f64::try_from(a)? + f64::try_from(b)?)),
         }
     }
 }
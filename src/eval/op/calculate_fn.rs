@@ -1,55 +1,270 @@
 mod trig;
 mod logs;
-use crate::eval::Data;
+mod derivative;
+mod number_theory;
+use crate::error::Error;
+use crate::eval::{diff::mul, op::root::NthRoot, Data};
+use crate::parser::ExprTree;
 use std::convert::TryFrom;
+pub use trig::AngleMode;
+pub(crate) use trig::{cos, sin};
 
 pub trait CalculateFn {
     type Output;
-    fn calculate_fn(self, fn_name: &String) -> Self::Output;
+    fn calculate_fn(self, fn_name: &String, angle_mode: AngleMode) -> Self::Output;
 }
 
-impl CalculateFn for Data {
-    type Output = Result<Self, String>;
-    fn calculate_fn(self, fn_name: &String) -> Self::Output {
-       let f = FunctionKind::try_from(fn_name).map(|fk| fk.as_function())?;
-       f(self)
+impl CalculateFn for &[Data] {
+    type Output = Result<Data, Error>;
+    fn calculate_fn(self, fn_name: &String, angle_mode: AngleMode) -> Self::Output {
+       let fk = FunctionKind::try_from(fn_name)?;
+       fk.check_arity(fn_name, self.len())?;
+       let f = fk.as_function();
+       let mut args = self.to_vec();
+       if fk.takes_angle() {
+           args = args
+               .into_iter()
+               .map(|x| angle_mode.to_radians(x))
+               .collect::<Result<_, _>>()?;
+       }
+       let result = f(args)?;
+       if fk.gives_angle() {
+           angle_mode.from_radians(result)
+       } else {
+           Ok(result)
+       }
     }
 }
 
+/// Is this the name of a built-in function
+pub fn is_function(name: &String) -> bool {
+    FunctionKind::try_from(name).is_ok()
+}
+
+/// The derivatives of a built-in function with respect to each of its arguments, for the chain rule
+pub fn partial_derivatives(
+    fn_name: &String,
+    args: &[ExprTree],
+    angle_mode: AngleMode,
+) -> Result<Vec<ExprTree>, Error> {
+    let fk = FunctionKind::try_from(fn_name)?;
+    fk.check_arity(fn_name, args.len())?;
+    let partials = fk.partial_derivatives(args)?;
+    // the rules are for radians, other units scale the derivative by how big a radian is in them
+    let radian = angle_mode.to_radians(Data::Int(1))?;
+    let scale = if fk.takes_angle() {
+        radian
+    } else if fk.gives_angle() {
+        (Data::Int(1) / radian)?
+    } else {
+        return Ok(partials);
+    };
+    Ok(partials
+        .into_iter()
+        .map(|p| mul(ExprTree::Val(scale.clone()), p))
+        .collect())
+}
+
 #[derive(Clone, Copy)]
 enum FunctionKind {
     Sin,
     Cos,
     Tan,
+    Asin,
+    Acos,
+    Atan,
+    Atan2,
+    Sec,
+    Csc,
+    Cot,
+    Sinh,
+    Cosh,
+    Tanh,
     Log10,
-    Ln
+    Log2,
+    Ln,
+    Sqrt,
+    Cbrt,
+    Root,
+    Max,
+    Min,
+    Gcd,
+    Lcm,
+    Mod,
+    IsPrime,
+    Factor,
+    Totient,
+    Ncr,
+    Npr,
+    Floor,
+    Ceil,
+    Round,
+    Abs,
+    Float,
 }
 
 impl TryFrom<&String> for FunctionKind {
-    type Error = String;
+    type Error = Error;
     fn try_from(word: &String) -> Result<Self, Self::Error> {
         Ok(match word.as_str() {
             "sin" => Self::Sin,
             "cos" => Self::Cos,
             "tan" => Self::Tan,
+            "asin" | "arcsin" => Self::Asin,
+            "acos" | "arccos" => Self::Acos,
+            "atan" | "arctan" => Self::Atan,
+            "atan2" => Self::Atan2,
+            "sec" => Self::Sec,
+            "csc" | "cosec" => Self::Csc,
+            "cot" => Self::Cot,
+            "sinh" => Self::Sinh,
+            "cosh" => Self::Cosh,
+            "tanh" => Self::Tanh,
             "log" => Self::Log10,
+            "log2" => Self::Log2,
             "ln" => Self::Ln,
-            unknown => {return Err(format!("unknown function: {}", unknown))}
+            "sqrt" => Self::Sqrt,
+            "cbrt" => Self::Cbrt,
+            "root" => Self::Root,
+            "max" => Self::Max,
+            "min" => Self::Min,
+            "gcd" | "hcf" => Self::Gcd,
+            "lcm" => Self::Lcm,
+            "mod" => Self::Mod,
+            "isprime" => Self::IsPrime,
+            "factor" | "factorise" => Self::Factor,
+            "totient" => Self::Totient,
+            "nCr" | "ncr" => Self::Ncr,
+            "nPr" | "npr" => Self::Npr,
+            "floor" => Self::Floor,
+            "ceil" => Self::Ceil,
+            "round" => Self::Round,
+            "abs" => Self::Abs,
+            "float" => Self::Float,
+            unknown => {return Err(Error::UnknownFunction(unknown.to_string()))}
         })
     }
-    
+
 }
 
-// type Function = impl FnOnce(Data) -> Result<Data, String>;
+// type Function = impl FnOnce(Data) -> Result<Data, Error>;
 
 impl FunctionKind {
-    fn as_function(&self) -> impl FnOnce(Data) -> Result<Data, String>  {
+    /// The fewest and the most arguments the function can take, `None` if there is no most
+    fn arity(&self) -> (usize, Option<usize>) {
+        match self {
+            Self::Log10 => (1, Some(2)),
+            Self::Atan2 | Self::Root | Self::Mod | Self::Ncr | Self::Npr => (2, Some(2)),
+            Self::Max | Self::Min => (1, None),
+            Self::Gcd | Self::Lcm => (2, None),
+            _ => (1, Some(1)),
+        }
+    }
+
+    /// does this function take an angle, which needs converting to radians first
+    fn takes_angle(&self) -> bool {
+        matches!(
+            self,
+            Self::Sin | Self::Cos | Self::Tan | Self::Sec | Self::Csc | Self::Cot
+        )
+    }
+
+    /// does this function give back an angle, which needs converting from radians
+    fn gives_angle(&self) -> bool {
+        matches!(self, Self::Asin | Self::Acos | Self::Atan | Self::Atan2)
+    }
+
+    fn check_arity(&self, fn_name: &String, given: usize) -> Result<(), Error> {
+        let (fewest, most) = self.arity();
+        let expected = match most {
+            Some(most) if given >= fewest && given <= most => return Ok(()),
+            None if given >= fewest => return Ok(()),
+            Some(most) if most == fewest => fewest.to_string(),
+            Some(most) => format!("{} or {}", fewest, most),
+            None => format!("at least {}", fewest),
+        };
+        Err(Error::Arity {
+            name: fn_name.clone(),
+            expected,
+            given,
+        })
+    }
+
+    /// the arguments passed to these have already had their arity checked
+    fn as_function(&self) -> impl FnOnce(Vec<Data>) -> Result<Data, Error>  {
         match self {
-            Self::Sin => |x| self::trig::sin(x),
-            Self::Cos => |x| self::trig::cos(x),
-            Self::Tan => |x| self::trig::tan(x),
-            Self::Ln => |x| self::logs::natural_log(x),
-            Self::Log10 => |x| self::logs::log_10(x),
+            Self::Sin => |mut x: Vec<Data>| self::trig::sin(x.remove(0)),
+            Self::Cos => |mut x: Vec<Data>| self::trig::cos(x.remove(0)),
+            Self::Tan => |mut x: Vec<Data>| self::trig::tan(x.remove(0)),
+            Self::Asin => |mut x: Vec<Data>| self::trig::asin(x.remove(0)),
+            Self::Acos => |mut x: Vec<Data>| self::trig::acos(x.remove(0)),
+            Self::Atan => |mut x: Vec<Data>| self::trig::atan(x.remove(0)),
+            Self::Sec => |mut x: Vec<Data>| self::trig::sec(x.remove(0)),
+            Self::Csc => |mut x: Vec<Data>| self::trig::csc(x.remove(0)),
+            Self::Cot => |mut x: Vec<Data>| self::trig::cot(x.remove(0)),
+            Self::Sinh => |mut x: Vec<Data>| self::trig::sinh(x.remove(0)),
+            Self::Cosh => |mut x: Vec<Data>| self::trig::cosh(x.remove(0)),
+            Self::Tanh => |mut x: Vec<Data>| self::trig::tanh(x.remove(0)),
+            Self::Atan2 => |mut x: Vec<Data>| {
+                let y = x.remove(0);
+                self::trig::atan2(y, x.remove(0))
+            },
+            Self::Ln => |mut x: Vec<Data>| self::logs::natural_log(x.remove(0)),
+            Self::Log2 => |mut x: Vec<Data>| self::logs::log_base(x.remove(0), Data::Int(2)),
+            Self::Log10 => |mut x: Vec<Data>| {
+                let n = x.remove(0);
+                match x.pop() {
+                    Some(base) => self::logs::log_base(n, base),
+                    None => self::logs::log_10(n),
+                }
+            },
+            Self::Sqrt => |mut x: Vec<Data>| x.remove(0).nth_root(2),
+            Self::Cbrt => |mut x: Vec<Data>| x.remove(0).nth_root(3),
+            Self::Root => |mut x: Vec<Data>| {
+                let n = x.remove(0);
+                match x.remove(0) {
+                    Data::Int(index) => n.nth_root(index),
+                    _ => Err(Error::domain("roots can only be taken to a whole number index")),
+                }
+            },
+            Self::Max => |x: Vec<Data>| extremum(x, std::cmp::Ordering::Greater),
+            Self::Min => |x: Vec<Data>| extremum(x, std::cmp::Ordering::Less),
+            Self::Gcd => |x: Vec<Data>| self::number_theory::gcd(x),
+            Self::Lcm => |x: Vec<Data>| self::number_theory::lcm(x),
+            Self::Mod => |mut x: Vec<Data>| {
+                let n = x.remove(0);
+                self::number_theory::modulo(n, x.remove(0))
+            },
+            Self::IsPrime => |mut x: Vec<Data>| self::number_theory::isprime(x.remove(0)),
+            Self::Factor => |mut x: Vec<Data>| self::number_theory::factor(x.remove(0)),
+            Self::Totient => |mut x: Vec<Data>| self::number_theory::totient(x.remove(0)),
+            Self::Ncr => |mut x: Vec<Data>| {
+                let n = x.remove(0);
+                self::number_theory::ncr(n, x.remove(0))
+            },
+            Self::Npr => |mut x: Vec<Data>| {
+                let n = x.remove(0);
+                self::number_theory::npr(n, x.remove(0))
+            },
+            Self::Floor => |mut x: Vec<Data>| self::number_theory::floor(x.remove(0)),
+            Self::Ceil => |mut x: Vec<Data>| self::number_theory::ceil(x.remove(0)),
+            Self::Round => |mut x: Vec<Data>| self::number_theory::round(x.remove(0)),
+            Self::Abs => |mut x: Vec<Data>| self::number_theory::abs(x.remove(0)),
+            Self::Float => |mut x: Vec<Data>| Ok(Data::Float(f64::try_from(x.remove(0))?)),
+        }
+    }
+}
+
+/// The biggest (or smallest) of the arguments, depending on `keep`
+fn extremum(args: Vec<Data>, keep: std::cmp::Ordering) -> Result<Data, Error> {
+    let mut args = args.into_iter();
+    let mut best = args.next().ok_or_else(|| Error::domain("there's nothing to compare"))?;
+    for arg in args {
+        match arg.partial_cmp(&best) {
+            Some(ordering) if ordering == keep => best = arg,
+            Some(_) => {}
+            None => return Err(Error::domain(format!("can't compare {} and {}", arg, best))),
         }
     }
+    Ok(best)
 }
@@ -0,0 +1,79 @@
+use crate::error::Error;
+use super::FunctionKind;
+use crate::eval::diff::{add, call, div, mul, neg, pow, sub, val};
+use crate::parser::ExprTree;
+
+impl FunctionKind {
+    /// The derivative with respect to each argument in turn, with angles in radians.
+    /// The arguments have already had their arity checked
+    pub(super) fn partial_derivatives(&self, args: &[ExprTree]) -> Result<Vec<ExprTree>, Error> {
+        let u = args[0].clone();
+        let f = |name: &str, arg: &ExprTree| call(name, vec![arg.clone()]);
+        let square = |t: ExprTree| pow(t, val(2));
+        Ok(match self {
+            Self::Sin => vec![f("cos", &u)],
+            Self::Cos => vec![neg(f("sin", &u))],
+            Self::Tan => vec![square(f("sec", &u))],
+            Self::Sec => vec![mul(f("sec", &u), f("tan", &u))],
+            Self::Csc => vec![neg(mul(f("csc", &u), f("cot", &u)))],
+            Self::Cot => vec![neg(square(f("csc", &u)))],
+            Self::Asin => vec![div(val(1), call("root", vec![sub(val(1), square(u)), val(2)]))],
+            Self::Acos => vec![neg(div(val(1), call("root", vec![sub(val(1), square(u)), val(2)])))],
+            Self::Atan => vec![div(val(1), add(val(1), square(u)))],
+            // atan2(y, x)
+            Self::Atan2 => {
+                let x = args[1].clone();
+                let r_squared = add(square(x.clone()), square(u.clone()));
+                vec![div(x, r_squared.clone()), neg(div(u, r_squared))]
+            }
+            Self::Sinh => vec![f("cosh", &u)],
+            Self::Cosh => vec![f("sinh", &u)],
+            Self::Tanh => vec![sub(val(1), square(f("tanh", &u)))],
+            Self::Ln => vec![div(val(1), u)],
+            Self::Log2 => vec![div(val(1), mul(u, f("ln", &val(2))))],
+            Self::Log10 => match args.get(1) {
+                None => vec![div(val(1), mul(u, f("ln", &val(10))))],
+                Some(base) => vec![
+                    div(val(1), mul(u.clone(), f("ln", base))),
+                    neg(div(f("ln", &u), mul(base.clone(), square(f("ln", base))))),
+                ],
+            },
+            Self::Sqrt => vec![div(val(1), mul(val(2), f("sqrt", &u)))],
+            Self::Cbrt => vec![div(val(1), mul(val(3), square(f("cbrt", &u))))],
+            // root(u, n) = u^(1/n)
+            Self::Root => {
+                let n = args[1].clone();
+                let root = call("root", args.to_vec());
+                vec![
+                    div(root.clone(), mul(n.clone(), u.clone())),
+                    neg(div(mul(root, f("ln", &u)), square(n))),
+                ]
+            }
+            Self::Float => vec![val(1)],
+            // d|u|/du = u/|u|, away from 0
+            Self::Abs => vec![div(u.clone(), f("abs", &u))],
+            Self::Max | Self::Min => {
+                return Err(Error::domain(
+                    "max and min can't be differentiated everywhere",
+                ))
+            }
+            Self::Floor | Self::Ceil | Self::Round => {
+                return Err(Error::domain(
+                    "floor, ceil and round jump at every whole number, so they can't be differentiated",
+                ))
+            }
+            Self::Gcd
+            | Self::Lcm
+            | Self::Mod
+            | Self::IsPrime
+            | Self::Factor
+            | Self::Totient
+            | Self::Ncr
+            | Self::Npr => {
+                return Err(Error::domain(
+                    "functions of whole numbers can't be differentiated",
+                ))
+            }
+        })
+    }
+}
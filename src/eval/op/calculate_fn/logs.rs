@@ -1,35 +1,35 @@
-use crate::eval::{Data, Symbolic};
+use crate::error::Error;
+use crate::eval::{logarithm::Logarithm, Data, Symbolic};
+use num::traits::One;
 
-type DataResult = Result<Data, String>;
+type DataResult = Result<Data, Error>;
 
 pub fn log_10(x: Data) -> DataResult {
+    log_base(x, Data::Int(10))
+}
+
+/// The logarithm of `x` to any base, exact whenever `x` is a rational power of the base,
+/// and kept as a logarithm if it isn't but they're both exact
+pub fn log_base(x: Data, base: Data) -> DataResult {
+    if base <= 0.into() || base == 1.into() {
+        return Err(Error::domain("logarithm to a base that is non-positive or 1"));
+    }
     if x <= 0.into() {
-        // x is positive
-        return Err("Error: Logarithm of a non-positive number".to_string());
+        return Err(Error::domain("logarithm of a non-positive number"));
     }
-    match x {
-        Data::Int(n) => {
-            if n % 10 == 0 {
-                Ok(Data::from((n as f64).log10() as i64)) // cast is sane because this should be a whole number
-            } else {
-                Ok(Data::from((n as f64).log10()))
-            }
-        }
-        Data::Float(n) => Ok(Data::Float(n.log10())),
-        Data::Rational(r) => {
-            // this is an optimisation for precision rather than speed, any cases where this would result in anything other than a float would get cancelled out anyway, but the more we can avoid floating-poing errors the better
-            let (numer, denom) = (*r.numer(), *r.denom());
-            log_10(Data::from(numer))? - log_10(Data::from(denom))?
-        }
-        otherwise => log_10(otherwise.as_float()?)
+    match base {
+        Data::Symbol(ref e) if e == "e" || e == "E" => natural_log(x),
+        base if Logarithm::keeps(&x) && Logarithm::keeps(&base) => Logarithm::of(x, base),
+        base => natural_log(x)? / natural_log(base)?,
     }
 }
 
 pub fn natural_log(x: Data) -> DataResult {
     if x <= 0.into() {
-        return Err("Error: Logarithm of a non-positive number".to_string())
+        return Err(Error::domain("logarithm of a non-positive number"))
     }
     match x {
+        Data::Float(n) => Ok(Data::Float(n.ln())),
         Data::Symbol(s) if s == "e" || s == "E" => Ok(Data::Int(1)),
         Data::Symbolic(s) => {
             let Symbolic {coeff, symbol, constant} = *s;
@@ -50,12 +50,15 @@ pub fn natural_log(x: Data) -> DataResult {
             } 
         }
         Data::Radical(r) => {
-            if r.coefficient == 1.into() && *r.radicand == Data::Symbol("e".into()) {
+            if r.coefficient.is_one() && *r.radicand == Data::Symbol("e".into()) {
                 Ok(Data::Rational((1, r.index as i64).into()))
+            } else if Logarithm::keeps(&r.radicand) {
+                Logarithm::of(Data::Radical(r), Data::Symbol("e".into()))
             } else {
                 r.as_float().map(|x| x.ln().into())
             }
         }
+        exact if Logarithm::keeps(&exact) => Logarithm::of(exact, Data::Symbol("e".into())),
         otherwise => natural_log(otherwise.as_float()?)
     }
 }
@@ -0,0 +1,267 @@
+/*! Functions of whole numbers and fractions, like gcd, factor and nCr, which all stay exact */
+
+use crate::error::Error;
+use crate::eval::op::factorial::BIGGEST_EXACT_FACTORIAL;
+use crate::eval::op::root::NthRoot;
+use crate::eval::primes::{factorise, is_prime_big, Factorisation};
+use crate::eval::Data;
+use num::bigint::BigInt;
+use num::integer::Integer;
+use num::rational::BigRational;
+use num::traits::{FromPrimitive, One, Signed, ToPrimitive, Zero};
+use std::cmp::Ordering;
+use std::convert::TryFrom;
+
+type DataResult = Result<Data, Error>;
+
+/// The biggest number that goes into all of them, for fractions it's the biggest fraction they're all whole multiples of
+pub fn gcd(args: Vec<Data>) -> DataResult {
+    let mut args = args.iter().map(|x| exact(x, "gcd"));
+    let mut result = args.next().unwrap()?;
+    for x in args {
+        let x = x?;
+        result = BigRational::new(result.numer().gcd(x.numer()), result.denom().lcm(x.denom()));
+    }
+    Ok(result.into())
+}
+
+/// The smallest number they all go into, for fractions it's the smallest fraction that's a whole multiple of them all
+pub fn lcm(args: Vec<Data>) -> DataResult {
+    let mut args = args.iter().map(|x| exact(x, "lcm"));
+    let mut result = args.next().unwrap()?;
+    for x in args {
+        let x = x?;
+        result = BigRational::new(result.numer().lcm(x.numer()), result.denom().gcd(x.denom()));
+    }
+    Ok(result.into())
+}
+
+/// What's left over after taking away as many `m`s as possible from `x`, this is never negative
+pub fn modulo(x: Data, m: Data) -> DataResult {
+    if m == Data::Int(0) {
+        return Err(Error::domain("Divide by 0"));
+    }
+    match (x.to_big_rational(), m.to_big_rational()) {
+        (Some(x), Some(m)) => {
+            let m = m.abs();
+            Ok((&x - &m * (&x / &m).floor()).into())
+        }
+        _ => Ok(Data::Float(f64::try_from(x)?.rem_euclid(f64::try_from(m)?))),
+    }
+}
+
+/// 1 if `x` is prime and 0 if it isn't, anything that isn't a whole number isn't prime
+pub fn isprime(x: Data) -> DataResult {
+    let n = match whole(&x) {
+        Some(n) if n.is_positive() => n,
+        _ => return Ok(Data::Int(0)),
+    };
+    Ok(Data::Int(is_prime_big(&n) as i64))
+}
+
+/// `x` broken down into primes, like `360 = 2^3 × 3^2 × 5`
+pub fn factor(x: Data) -> DataResult {
+    let n = whole(&x).ok_or_else(|| Error::domain(format!("only whole numbers can be factorised, not {}", x)))?;
+    if n.is_zero() {
+        return Err(Error::domain("0 can't be factorised, everything goes into it"));
+    }
+    Ok(Factorisation::of(&n)?.into())
+}
+
+/// How many of the numbers from 1 to `x` have nothing in common with it
+pub fn totient(x: Data) -> DataResult {
+    let n = match whole(&x) {
+        Some(n) if n.is_positive() => n,
+        _ => return Err(Error::domain(format!("totient({}) isn't defined, it needs a positive whole number", x))),
+    };
+    let n = n
+        .to_u64()
+        .ok_or_else(|| Error::Overflow(format!("{} is too big to find the totient of", n)))?;
+    // φ(p^k) = p^(k - 1) (p - 1), and it multiplies across the prime powers
+    Ok(factorise(n)
+        .into_iter()
+        .map(|(p, k)| BigInt::from(p).pow(k - 1) * (p - 1))
+        .product::<BigInt>()
+        .into())
+}
+
+/// The number of ways to choose `r` things from `n`, when the order doesn't matter
+pub fn ncr(n: Data, r: Data) -> DataResult {
+    let (n, r) = choices(n, r, "nCr")?;
+    if r > n {
+        return Ok(Data::Int(0));
+    }
+    // nCr = nC(n - r), so take whichever has fewer steps
+    let r = r.clone().min(&n - &r);
+    let steps = step_count(&r, "nCr")?;
+    let mut result = BigInt::one();
+    for i in 0..steps {
+        // each partial product is itself a binomial coefficient, so this division is always exact
+        result = result * (&n - i) / (i + 1);
+    }
+    Ok(result.into())
+}
+
+/// The number of ways to arrange `r` things out of `n`, when the order does matter
+pub fn npr(n: Data, r: Data) -> DataResult {
+    let (n, r) = choices(n, r, "nPr")?;
+    if r > n {
+        return Ok(Data::Int(0));
+    }
+    let steps = step_count(&r, "nPr")?;
+    Ok((0..steps).map(|i| &n - i).product::<BigInt>().into())
+}
+
+pub fn floor(x: Data) -> DataResult {
+    to_whole(x, BigRational::floor, f64::floor)
+}
+
+pub fn ceil(x: Data) -> DataResult {
+    to_whole(x, BigRational::ceil, f64::ceil)
+}
+
+/// to the nearest whole number, halves go away from 0
+pub fn round(x: Data) -> DataResult {
+    to_whole(x, BigRational::round, f64::round)
+}
+
+/// How far `x` is from 0, for complex numbers this is the modulus
+pub fn abs(x: Data) -> DataResult {
+    if let Some(r) = x.to_big_rational() {
+        return Ok(r.abs().into());
+    }
+    match x {
+        Data::Complex(z) => {
+            let (re, im) = (z.re, z.im);
+            ((re.clone() * re)? + (im.clone() * im)?)?.nth_root(2)
+        }
+        x => match x.partial_cmp(&Data::Int(0)) {
+            Some(Ordering::Less) => Ok(-x),
+            Some(_) => Ok(x),
+            None => Err(Error::algebra(format!("can't tell whether {} is positive or negative", x))),
+        },
+    }
+}
+
+/// `x` as an exact fraction, for the functions that only make sense that way
+fn exact(x: &Data, fn_name: &str) -> Result<BigRational, Error> {
+    x.to_big_rational()
+        .ok_or_else(|| Error::domain(format!("{} only works on whole numbers and fractions, not {}", fn_name, x)))
+}
+
+/// `Some(n)` if `x` is the whole number n, floats count if they have nothing after the point
+fn whole(x: &Data) -> Option<BigInt> {
+    match x {
+        Data::Float(f) if f.fract() == 0. => BigInt::from_f64(*f),
+        x => x.to_big_rational().filter(|r| r.is_integer()).map(|r| r.to_integer()),
+    }
+}
+
+/// Checks the `n` and `r` of `nCr` or `nPr` are whole numbers that aren't negative
+fn choices(n: Data, r: Data, fn_name: &str) -> Result<(BigInt, BigInt), Error> {
+    match (whole(&n), whole(&r)) {
+        (Some(n), Some(r)) if !n.is_negative() && !r.is_negative() => Ok((n, r)),
+        _ => Err(Error::domain(format!(
+            "{}({}, {}) isn't defined, both need to be whole numbers that aren't negative",
+            fn_name, n, r
+        ))),
+    }
+}
+
+/// How many multiplications working out `nCr` or `nPr` will take, with the same limit as factorials
+fn step_count(r: &BigInt, fn_name: &str) -> Result<i64, Error> {
+    match r.to_i64() {
+        Some(steps) if steps <= BIGGEST_EXACT_FACTORIAL => Ok(steps),
+        _ => Err(Error::Overflow(format!(
+            "{} is too big, it can only choose up to {} things",
+            fn_name, BIGGEST_EXACT_FACTORIAL
+        ))),
+    }
+}
+
+/// Rounds `x` to a whole number, exactly if it's a fraction and through a float if it's anything else
+fn to_whole(
+    x: Data,
+    exact: impl FnOnce(&BigRational) -> BigRational,
+    float: impl FnOnce(f64) -> f64,
+) -> DataResult {
+    if let Some(r) = x.to_big_rational() {
+        return Ok(exact(&r).into());
+    }
+    let rounded = float(f64::try_from(x)?);
+    BigInt::from_f64(rounded)
+        .map(Data::from)
+        .ok_or_else(|| Error::Overflow(format!("{} isn't a finite number", rounded)))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::eval::op::pow::Pow;
+
+    fn ints(xs: &[i64]) -> Vec<Data> {
+        xs.iter().map(|x| Data::Int(*x)).collect()
+    }
+
+    #[test]
+    fn gcd_and_lcm() {
+        assert_eq!(gcd(ints(&[12, 18, 30])), Ok(Data::Int(6)));
+        assert_eq!(lcm(ints(&[4, 6])), Ok(Data::Int(12)));
+        let (half, third) = (Data::Rational((1, 2).into()), Data::Rational((2, 3).into()));
+        assert_eq!(gcd(vec![half.clone(), third.clone()]), Ok(Data::Rational((1, 6).into())));
+        assert_eq!(lcm(vec![half, third]), Ok(Data::Int(2)));
+        assert!(gcd(vec![Data::Int(2), Data::Float(0.5)]).is_err());
+    }
+
+    #[test]
+    fn modulo_is_never_negative() {
+        assert_eq!(modulo(Data::Int(7), Data::Int(3)), Ok(Data::Int(1)));
+        assert_eq!(modulo(Data::Int(-7), Data::Int(3)), Ok(Data::Int(2)));
+        assert_eq!(modulo(Data::Int(7), Data::Int(-3)), Ok(Data::Int(1)));
+        assert_eq!(modulo(Data::Rational((7, 2).into()), Data::Int(1)), Ok(Data::Rational((1, 2).into())));
+        assert!(modulo(Data::Int(1), Data::Int(0)).is_err());
+    }
+
+    #[test]
+    fn primes_and_factors() {
+        assert_eq!(isprime(Data::Int(97)), Ok(Data::Int(1)));
+        assert_eq!(isprime(Data::Int(91)), Ok(Data::Int(0)));
+        assert_eq!(isprime(Data::Rational((1, 2).into())), Ok(Data::Int(0)));
+        assert_eq!(format!("{}", factor(Data::Int(360)).unwrap()), "2^3 × 3^2 × 5");
+        assert_eq!(format!("{}", factor(Data::Int(-7)).unwrap()), "-7");
+        assert_eq!(format!("{}", factor(Data::Int(1)).unwrap()), "1");
+        assert!(factor(Data::Int(0)).is_err());
+        assert_eq!(totient(Data::Int(36)), Ok(Data::Int(12)));
+        // past a u64
+        let mersenne = (Data::Int(2).pow(Data::Int(89)).unwrap() - Data::Int(1)).unwrap();
+        assert_eq!(isprime(mersenne.clone()), Ok(Data::Int(1)));
+        assert_eq!(isprime((mersenne.clone() * Data::Int(3)).unwrap()), Ok(Data::Int(0)));
+        assert_eq!(
+            format!("{}", factor((mersenne * Data::Int(12)).unwrap()).unwrap()),
+            "2^2 × 3 × 618970019642690137449562111"
+        );
+    }
+
+    #[test]
+    fn choosing_and_arranging() {
+        assert_eq!(ncr(Data::Int(5), Data::Int(2)), Ok(Data::Int(10)));
+        assert_eq!(ncr(Data::Int(2), Data::Int(5)), Ok(Data::Int(0)));
+        assert_eq!(npr(Data::Int(5), Data::Int(2)), Ok(Data::Int(20)));
+        assert_eq!(
+            format!("{}", ncr(Data::Int(100), Data::Int(50)).unwrap()),
+            "100891344545564193334812497256"
+        );
+        assert!(ncr(Data::Int(-1), Data::Int(2)).is_err());
+    }
+
+    #[test]
+    fn rounding() {
+        let x = Data::Rational((-7, 2).into());
+        assert_eq!(floor(x.clone()), Ok(Data::Int(-4)));
+        assert_eq!(ceil(x.clone()), Ok(Data::Int(-3)));
+        assert_eq!(round(x.clone()), Ok(Data::Int(-4)));
+        assert_eq!(abs(x), Ok(Data::Rational((7, 2).into())));
+        assert_eq!(floor(Data::Symbol("pi".into())), Ok(Data::Int(3)));
+        assert_eq!(abs(Data::Float(-1.5)), Ok(Data::Float(1.5)));
+    }
+}
@@ -1,37 +1,161 @@
-use crate::eval::{ratio_as_float, Data, Radical, SymbolEval, Symbolic};
+use crate::error::Error;
+use crate::eval::{big::big, ratio_as_float, Data, Radical, SymbolEval, Symbolic};
 use num::rational::Ratio;
+use num::traits::One;
 use std::convert::TryFrom;
 
-type DataResult = Result<Data, String>;
+type DataResult = Result<Data, Error>;
 
-pub fn sin(data: Data) -> DataResult {
-    fn sin_pi_coeff_lookup(c: &Data) -> Option<Data> {
-        Some(match c {
-            Data::Rational(r) => match (*r.numer(), *r.denom()) {
-                (1, 6) => Data::Rational((1, 2).into()),
-                (1, 4) => Data::Radical(Radical::new_raw((1, 2).into(), 2, Data::from(2).into())),
-                (1, 3) => Data::Radical(Radical::new_raw((1, 2).into(), 2, Data::from(3).into())),
-                (1, 2) => Data::Int(1),
-                (1, 10) => Data::Symbolic(
-                    Symbolic {
-                        coeff: Data::Rational((1, 2).into()).into(),
-                        symbol: "phi".into(),
-                        constant: Data::Rational((-1, 2).into()).into(),
-                    }
-                    .into(),
-                ),
-                _ => return None,
-            },
-            Data::Int(_) => Data::Int(0),
-            _ => return None,
+/// What unit angles are measured in, for the whole session
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub enum AngleMode {
+    #[default]
+    Radians,
+    Degrees,
+    Gradians,
+}
+
+impl std::str::FromStr for AngleMode {
+    type Err = Error;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Ok(match s {
+            "rad" | "radians" => Self::Radians,
+            "deg" | "degrees" => Self::Degrees,
+            "grad" | "gradians" => Self::Gradians,
+            unknown => {
+                return Err(Error::invalid_argument(format!(
+                    "there's no angle mode called {}, the modes are rad, deg and grad",
+                    unknown
+                )))
+            }
         })
     }
+}
+
+impl std::fmt::Display for AngleMode {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{}",
+            match self {
+                Self::Radians => "radians",
+                Self::Degrees => "degrees",
+                Self::Gradians => "gradians",
+            }
+        )
+    }
+}
+
+impl AngleMode {
+    /// How many of this unit make half a turn, or `None` for radians, which is :pi
+    fn half_turn(&self) -> Option<i64> {
+        match self {
+            Self::Radians => None,
+            Self::Degrees => Some(180),
+            Self::Gradians => Some(200),
+        }
+    }
+
+    /// Converts an angle in this unit to radians, exactly, so `30` degrees becomes `1/6 :pi` and hits the lookup tables
+    pub fn to_radians(&self, angle: Data) -> DataResult {
+        match self.half_turn() {
+            None => Ok(angle),
+            Some(half_turn) => (angle * Data::Symbol("pi".into()))? / Data::Int(half_turn),
+        }
+    }
+
+    /// Converts an angle in radians to this unit, exact multiples of pi come out exact
+    pub fn from_radians(&self, angle: Data) -> DataResult {
+        let half_turn = match self.half_turn() {
+            None => return Ok(angle),
+            Some(h) => h,
+        };
+        match angle {
+            Data::Int(0) => Ok(Data::Int(0)),
+            Data::Symbol(s) if s == "pi" => Ok(Data::Int(half_turn)),
+            Data::Symbolic(s) if s.symbol == "pi" && s.constant.is_none() => {
+                s.coeff.unwrap_or(Data::Int(1)) * Data::Int(half_turn)
+            }
+            otherwise => Ok(Data::Float(
+                f64::try_from(otherwise)? * half_turn as f64 / std::f64::consts::PI,
+            )),
+        }
+    }
+}
+
+/// sin of `c`:pi, for the special values of `c` in the first quadrant
+fn sin_pi_coeff_lookup(c: &Data) -> Option<Data> {
+    Some(match c {
+        Data::Rational(r) => match (*r.numer(), *r.denom()) {
+            (1, 6) => Data::Rational((1, 2).into()),
+            (1, 4) => Data::Radical(Radical::new_raw(big((1, 2).into()), 2, Data::from(2).into())),
+            (1, 3) => Data::Radical(Radical::new_raw(big((1, 2).into()), 2, Data::from(3).into())),
+            (1, 2) => Data::Int(1),
+            (1, 10) => Data::Symbolic(
+                Symbolic {
+                    coeff: Data::Rational((1, 2).into()).into(),
+                    symbol: "phi".into(),
+                    constant: Data::Rational((-1, 2).into()).into(),
+                }
+                .into(),
+            ),
+            _ => return None,
+        },
+        Data::Int(_) => Data::Int(0),
+        _ => return None,
+    })
+}
+
+/// cos of `c`:pi, for the special values of `c` in the first quadrant
+fn cos_pi_coeff_lookup(c: &Data) -> Option<Data> {
+    Some(match c {
+        Data::Rational(r) => match (*r.numer(), *r.denom()) {
+            (1, 6) => Data::Radical(Radical::new_raw(big((1, 2).into()), 2, Data::from(3).into())),
+            (1, 4) => Data::Radical(Radical::new_raw(big((1, 2).into()), 2, Data::from(2).into())),
+            (1, 3) => Data::Rational((1, 2).into()),
+            (1, 2) => Data::Int(0),
+            (1, 5) => Data::Symbolic(
+                Symbolic {
+                    coeff: Some(Data::Rational((1, 2).into())),
+                    symbol: "phi".into(),
+                    constant: None,
+                }
+                .into(),
+            ),
+            _ => return None,
+        },
+        Data::Int(_) => Data::Int(1), // this seems weird but remember that this is only being called in quadrant 1
+        _ => return None,
+    })
+}
+
+/// tan of `coeff`:pi, for the special values of `coeff` in the first quadrant
+fn tan_pi_coeff_lookup(coeff: Ratio<i64>) -> Option<Data> {
+    Some(match (*coeff.numer(), *coeff.denom()) {
+        (1, 6) => Data::Radical(Radical::new_raw(big((1, 3).into()), 2, Data::from(3).into())),
+        (1, 4) => Data::Int(1),
+        (1, 3) => Data::Radical(Radical::new_raw(One::one(), 2, Data::from(3).into())),
+        (1, 2) => return None,
+        _ => return None,
+    })
+}
+
+pub fn sin(data: Data) -> DataResult {
     Ok(match data {
         Data::Int(0) => Data::Int(0),
         Data::Int(n) => (n as f64).sin().into(),
         Data::Float(n) => n.sin().into(),
         Data::Radical(n) => n.as_float()?.sin().into(),
         Data::Rational(n) => ratio_as_float(n).sin().into(),
+        other @ (Data::BigInt(_)
+        | Data::BigRational(_)
+        | Data::Polynomial(_)
+        | Data::Complex(_)
+        | Data::RadicalSum(_)
+        | Data::Log(_)
+        | Data::Factorisation(_)) => {
+            f64::try_from(other)?.sin().into()
+        }
         Data::Symbol(pi) if pi == "pi" => Data::Int(0),
         Data::Symbol(s) => s.symbol_eval()?.sin().into(),
         Data::Symbolic(a) => match *a {
@@ -41,16 +165,8 @@ pub fn sin(data: Data) -> DataResult {
                 constant: None,
             } if symbol == "pi" => {
                 // if in terms of pi
-                if coeff
-                    <= Data::Symbolic(
-                        Symbolic {
-                            coeff: Some(Data::Rational(Ratio::from((1, 2)))),
-                            symbol: "pi".into(),
-                            constant: None,
-                        }
-                        .into(),
-                    )
-                    && coeff >= Data::Int(0)
+                // the coefficient is already the multiple of pi, so quadrant 1 is up to 1/2 of it
+                if coeff <= Data::Rational(Ratio::from((1, 2))) && coeff >= Data::Int(0)
                 {
                     // if in quadrant 1
                     if let Some(ret) = sin_pi_coeff_lookup(&coeff) {
@@ -79,57 +195,27 @@ pub fn sin(data: Data) -> DataResult {
                         Data::Rational(r) => {
                             let in_unit = r % 2;
                             let not_negative = if in_unit < 0.into() {
-                                Ratio::from(2) - in_unit
+                                in_unit + 2
                             } else {
                                 in_unit
                             };
                             // for the unit circle, if :theta = k*:pi, 0 < k < 2, if 0 < k < 1, sin :theta is positive
                             // the values in the 1st and 2nd quadrants are equal, 3rd and 4th are too, reflected about the y axis
-                            if not_negative < 1.into() {
-                                let reflected = if not_negative <= (1, 2).into() {
-                                    not_negative
-                                } else {
-                                    Ratio::from(1) - not_negative
-                                };
-                                if let Some(n) = sin_pi_coeff_lookup(&Data::Rational(reflected)) {
-                                    n
-                                } else {
-                                    f64::try_from(Data::Symbolic(
-                                        // otherwise just do it as a float
-                                        Symbolic {
-                                            coeff: Some(coeff),
-                                            symbol,
-                                            constant: None,
-                                        }
-                                        .into(),
-                                    ))?
-                                    .sin()
-                                    .into()
-                                }
+                            let (rotated, negative) = if not_negative > 1.into() {
+                                (not_negative - 1, true)
+                            } else {
+                                (not_negative, false)
+                            };
+                            let reflected = if rotated <= (1, 2).into() {
+                                rotated
                             } else {
-                                // must be in quadrant 3 or 4
-                                let rotated = not_negative - 1;
-                                let reflected = if rotated <= (1, 2).into() {
-                                    rotated
-                                } else {
-                                    Ratio::from(1) - rotated
-                                };
-                                if let Some(n) = sin_pi_coeff_lookup(&Data::Rational(reflected)) {
-                                    -n
-                                } else {
-                                    Data::from(
-                                        -(f64::try_from(Data::Symbolic(
-                                            // otherwise just do it as a float
-                                            Symbolic {
-                                                coeff: Some(coeff),
-                                                symbol,
-                                                constant: None,
-                                            }
-                                            .into(),
-                                        ))?
-                                        .sin()),
-                                    )
-                                }
+                                Ratio::from(1) - rotated
+                            };
+                            match sin_pi_coeff_lookup(&Data::Rational(reflected)) {
+                                Some(n) if negative => -n,
+                                Some(n) => n,
+                                // otherwise just do it as a float
+                                None => Data::from((ratio_as_float(r) * std::f64::consts::PI).sin()),
                             }
                         }
                         a => Data::from((f64::try_from(a)? * std::f64::consts::PI).sin()),
@@ -142,34 +228,22 @@ pub fn sin(data: Data) -> DataResult {
 }
 
 pub fn cos(data: Data) -> DataResult {
-    fn cos_pi_coeff_lookup(c: &Data) -> Option<Data> {
-        Some(match c {
-            Data::Rational(r) => match (*r.numer(), *r.denom()) {
-                (1, 6) => Data::Radical(Radical::new_raw((1, 2).into(), 2, Data::from(3).into())),
-                (1, 4) => Data::Radical(Radical::new_raw((1, 2).into(), 2, Data::from(2).into())),
-                (1, 3) => Data::Rational((1, 2).into()),
-                (1, 2) => Data::Int(0),
-                (1, 5) => Data::Symbolic(
-                    Symbolic {
-                        coeff: Some(Data::Rational((1, 2).into())),
-                        symbol: "phi".into(),
-                        constant: None,
-                    }
-                    .into(),
-                ),
-                _ => return None,
-            },
-            Data::Int(_) => Data::Int(1), // this seems weird but remember that this is only being called in quadrant 1
-            _ => return None,
-        })
-    }
     Ok(match data {
         Data::Int(0) => Data::Int(1),
         Data::Int(n) => (n as f64).cos().into(),
-        Data::Float(n) => n.sin().into(),
-        Data::Rational(n) => ratio_as_float(n).sin().into(),
+        Data::Float(n) => n.cos().into(),
+        Data::Rational(n) => ratio_as_float(n).cos().into(),
         Data::Radical(n) => n.as_float()?.cos().into(),
-        Data::Symbol(pi) if pi == "pi" => Data::Int(0),
+        other @ (Data::BigInt(_)
+        | Data::BigRational(_)
+        | Data::Polynomial(_)
+        | Data::Complex(_)
+        | Data::RadicalSum(_)
+        | Data::Log(_)
+        | Data::Factorisation(_)) => {
+            f64::try_from(other)?.cos().into()
+        }
+        Data::Symbol(pi) if pi == "pi" => Data::Int(-1),
         Data::Symbol(s) => s.symbol_eval()?.cos().into(),
         Data::Symbolic(a) => match *a {
             Symbolic {
@@ -178,16 +252,8 @@ pub fn cos(data: Data) -> DataResult {
                 constant: None,
             } if symbol == "pi" => {
                 // if in terms of pi
-                if coeff
-                    <= Data::Symbolic(
-                        Symbolic {
-                            coeff: Some(Data::Rational(Ratio::from((1, 2)))),
-                            symbol: "pi".into(),
-                            constant: None,
-                        }
-                        .into(),
-                    )
-                    && coeff >= Data::Int(0)
+                // the coefficient is already the multiple of pi, so quadrant 1 is up to 1/2 of it
+                if coeff <= Data::Rational(Ratio::from((1, 2))) && coeff >= Data::Int(0)
                 {
                     // if in quadrant 1
                     if let Some(ret) = cos_pi_coeff_lookup(&coeff) {
@@ -203,7 +269,7 @@ pub fn cos(data: Data) -> DataResult {
                             }
                             .into(),
                         ))?
-                        .sin()
+                        .cos()
                         .into()
                     }
                 } else {
@@ -220,60 +286,24 @@ pub fn cos(data: Data) -> DataResult {
                         Data::Rational(r) => {
                             let in_unit = r % 2;
                             let not_negative = if in_unit < 0.into() {
-                                Ratio::from(2) - in_unit
+                                in_unit + 2
                             } else {
                                 in_unit
                             };
-                            if not_negative <= (1, 2).into() || not_negative >= (3, 2).into() {
-                                // quadrants 1 and 4
-                                let reflected = if not_negative <= (1, 2).into() {
-                                    not_negative
-                                } else {
-                                    Ratio::from(2) - not_negative // reflect quadrant 4 about the x axis
-                                };
-                                if let Some(n) = cos_pi_coeff_lookup(&Data::Rational(reflected)) {
-                                    n
-                                } else {
-                                    f64::try_from(Data::Symbolic(
-                                        // otherwise just do it as a float
-                                        Symbolic {
-                                            coeff: Some(coeff),
-                                            symbol,
-                                            constant: None,
-                                        }
-                                        .into(),
-                                    ))?
-                                    .cos()
-                                    .into()
-                                }
+                            // cos is the same either side of the x axis, so quadrants 3 and 4 reflect onto 2 and 1
+                            let reflected = if not_negative > 1.into() {
+                                Ratio::from(2) - not_negative
                             } else {
-                                // must be in quadrant 2 or 3
-                                let rotated = if not_negative > 1.into() {
-                                    // quadrant 2
-                                    Ratio::from(1) - not_negative
-                                } else if not_negative > (3, 2).into() {
-                                    // quadrant 3
-                                    not_negative - Ratio::from(1)
-                                } else {
-                                    unreachable!()
-                                };
-                                if let Some(n) = cos_pi_coeff_lookup(&Data::Rational(rotated)) {
-                                    -n
-                                } else {
-                                    Data::from(
-                                        -(f64::try_from(Data::Symbolic(
-                                            // otherwise just do it as a float
-                                            Symbolic {
-                                                coeff: Some(coeff),
-                                                symbol,
-                                                constant: None,
-                                            }
-                                            .into(),
-                                        ))?
-                                        .cos()),
-                                    )
-                                }
-                            }
+                                not_negative
+                            };
+                            // and quadrant 2 is quadrant 1 reflected about the y axis, with the sign flipped
+                            let looked_up = if reflected <= (1, 2).into() {
+                                cos_pi_coeff_lookup(&Data::Rational(reflected))
+                            } else {
+                                cos_pi_coeff_lookup(&Data::Rational(Ratio::from(1) - reflected)).map(|n| -n)
+                            };
+                            // otherwise just do it as a float
+                            looked_up.unwrap_or_else(|| Data::from((ratio_as_float(r) * std::f64::consts::PI).cos()))
                         }
                         a => Data::from((f64::try_from(a)? * std::f64::consts::PI).cos()),
                     }
@@ -285,16 +315,6 @@ pub fn cos(data: Data) -> DataResult {
 }
 
 pub fn tan(theta: Data) -> DataResult {
-    fn tan_pi_coeff_lookup(coeff: Ratio<i64>) -> Option<Data> {
-        Some(match (*coeff.numer(), *coeff.denom()) {
-            (1, 6) => Data::Radical(Radical::new_raw((1, 3).into(), 2, Data::from(3).into())),
-            (1, 4) => Data::Int(1),
-            (1, 3) => Data::Radical(Radical::new_raw(1.into(), 2, Data::from(3).into())),
-            (1, 2) => return None,
-            _ => return None,
-        })
-    }
-
     match theta {
         Data::Int(0) => Ok(Data::Int(0)),
         Data::Symbolic(a) => match *a {
@@ -307,19 +327,19 @@ pub fn tan(theta: Data) -> DataResult {
                 Data::Rational(r) => {
                     let r = r % 1;
                     if r == (1, 2).into() || r == (-1, 2).into() {
-                        return Err("Undefined: Tangent of 1/2".to_string())
+                        return Err(Error::domain("the tangent is undefined where the cosine is 0"))
                     }
                     let looked_up = if r < 0.into() {
-                        tan_pi_coeff_lookup(r).map(|x| -x)
+                        tan_pi_coeff_lookup(-r).map(|x| -x)
                     } else {
                         tan_pi_coeff_lookup(r)
                     };                      
                     if let Some(res) = looked_up {
                         Ok(res)
                     }
-                    else {sin(Data::Rational(r.clone()))? / cos(Data::Rational(r))?}
+                    else {sin(pi_times(r))? / cos(pi_times(r))?}
                 },
-                otherwise => Ok(f64::try_from(otherwise)?.tan().into())
+                otherwise => Ok((f64::try_from(otherwise)? * std::f64::consts::PI).tan().into())
             },
             otherwise => {
                 Ok(otherwise.as_float()?.tan().into())
@@ -328,3 +348,220 @@ pub fn tan(theta: Data) -> DataResult {
         otherwise => sin(otherwise.clone())? / cos(otherwise)?
     }
 }
+
+/// The angle from the positive x axis to the point (x, y)
+pub fn atan2(y: Data, x: Data) -> DataResult {
+    if y == Data::Int(0) && x == Data::Int(0) {
+        return Err(Error::domain("atan2 of the origin is undefined"));
+    }
+    Ok(f64::try_from(y)?.atan2(f64::try_from(x)?).into())
+}
+
+/// `c`:pi, tidied up so that 0 and 1 don't become Symbolics
+fn pi_times(c: Ratio<i64>) -> Data {
+    if c == 0.into() {
+        Data::Int(0)
+    } else if c == 1.into() {
+        Data::Symbol("pi".into())
+    } else {
+        Data::Symbolic(
+            Symbolic {
+                coeff: Some(Data::from_ratio(c)),
+                symbol: "pi".into(),
+                constant: None,
+            }
+            .into(),
+        )
+    }
+}
+
+/// The multiples of pi that have special values in the lookup tables
+const SPECIAL_PI_COEFFS: [(i64, i64); 7] = [(0, 1), (1, 10), (1, 6), (1, 5), (1, 4), (1, 3), (1, 2)];
+
+/// Searches a first-quadrant lookup table for the angle that gives `value`, the inverse of the lookup
+fn inverse_lookup(value: &Data, lookup: impl Fn(Ratio<i64>) -> Option<Data>) -> Option<Ratio<i64>> {
+    SPECIAL_PI_COEFFS
+        .iter()
+        .map(|&c| Ratio::from(c))
+        .find(|c| lookup(*c).as_ref() == Some(value))
+}
+
+fn as_coeff(c: Ratio<i64>) -> Data {
+    if c == 0.into() {
+        Data::Int(0)
+    } else {
+        Data::Rational(c)
+    }
+}
+
+fn check_unit_interval(fn_name: &str, x: &Data) -> Result<(), Error> {
+    if x > &Data::Int(1) || x < &Data::Int(-1) {
+        Err(Error::non_real(format!("{} of a number outside of [-1, 1]", fn_name)))
+    } else {
+        Ok(())
+    }
+}
+
+pub fn asin(x: Data) -> DataResult {
+    check_unit_interval("asin", &x)?;
+    // sin is odd, so we only need the table for positive values
+    let negative = x < Data::Int(0);
+    let abs_x = if negative { -x.clone() } else { x.clone() };
+    Ok(
+        match inverse_lookup(&abs_x, |c| sin_pi_coeff_lookup(&as_coeff(c))) {
+            Some(c) => pi_times(if negative { -c } else { c }),
+            None => f64::try_from(x)?.asin().into(),
+        },
+    )
+}
+
+pub fn acos(x: Data) -> DataResult {
+    check_unit_interval("acos", &x)?;
+    // acos(-x) = pi - acos(x)
+    let negative = x < Data::Int(0);
+    let abs_x = if negative { -x.clone() } else { x.clone() };
+    Ok(
+        match inverse_lookup(&abs_x, |c| cos_pi_coeff_lookup(&as_coeff(c))) {
+            Some(c) => pi_times(if negative { Ratio::from(1) - c } else { c }),
+            None => f64::try_from(x)?.acos().into(),
+        },
+    )
+}
+
+pub fn atan(x: Data) -> DataResult {
+    // tan is odd too
+    let negative = x < Data::Int(0);
+    let abs_x = if negative { -x.clone() } else { x.clone() };
+    let lookup = |c: Ratio<i64>| {
+        if c == 0.into() {
+            Some(Data::Int(0))
+        } else {
+            tan_pi_coeff_lookup(c)
+        }
+    };
+    Ok(match inverse_lookup(&abs_x, lookup) {
+        Some(c) => pi_times(if negative { -c } else { c }),
+        None => f64::try_from(x)?.atan().into(),
+    })
+}
+
+pub fn sec(theta: Data) -> DataResult {
+    Data::Int(1) / cos(theta)?
+}
+
+pub fn csc(theta: Data) -> DataResult {
+    Data::Int(1) / sin(theta)?
+}
+
+pub fn cot(theta: Data) -> DataResult {
+    cos(theta.clone())? / sin(theta)?
+}
+
+pub fn sinh(x: Data) -> DataResult {
+    Ok(match x {
+        Data::Int(0) => Data::Int(0),
+        otherwise => f64::try_from(otherwise)?.sinh().into(),
+    })
+}
+
+pub fn cosh(x: Data) -> DataResult {
+    Ok(match x {
+        Data::Int(0) => Data::Int(1),
+        otherwise => f64::try_from(otherwise)?.cosh().into(),
+    })
+}
+
+pub fn tanh(x: Data) -> DataResult {
+    Ok(match x {
+        Data::Int(0) => Data::Int(0),
+        otherwise => f64::try_from(otherwise)?.tanh().into(),
+    })
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn root(coeff: (i64, i64), radicand: i64) -> Data {
+        Data::Radical(Radical::new_raw(big(coeff.into()), 2, Data::from(radicand).into()))
+    }
+
+    #[test]
+    fn inverses_of_special_values_are_exact() {
+        assert_eq!(asin(Data::Rational((1, 2).into())), Ok(pi_times((1, 6).into())));
+        assert_eq!(asin(Data::Int(-1)), Ok(pi_times((-1, 2).into())));
+        assert_eq!(acos(root((1, 2), 2)), Ok(pi_times((1, 4).into())));
+        assert_eq!(acos(Data::Rational((-1, 2).into())), Ok(pi_times((2, 3).into())));
+        assert_eq!(atan(root((1, 1), 3)), Ok(pi_times((1, 3).into())));
+        assert_eq!(atan(Data::Int(-1)), Ok(pi_times((-1, 4).into())));
+    }
+
+    #[test]
+    fn reciprocals_of_special_values_are_exact() {
+        assert_eq!(sec(pi_times((1, 6).into())), Ok(root((2, 3), 3)));
+        assert_eq!(csc(pi_times((1, 4).into())), Ok(root((1, 1), 2)));
+        assert_eq!(csc(pi_times((1, 3).into())), Ok(root((2, 3), 3)));
+        assert_eq!(cot(pi_times((1, 3).into())), Ok(root((1, 3), 3)));
+    }
+
+    #[test]
+    fn tan_in_every_quadrant() {
+        assert_eq!(tan(pi_times((5, 6).into())), Ok(root((-1, 3), 3)));
+        assert_eq!(tan(pi_times((-5, 6).into())), Ok(root((1, 3), 3)));
+        assert_eq!(tan(pi_times((2, 3).into())), Ok(root((-1, 1), 3)));
+        assert_eq!(tan(pi_times((4, 3).into())), Ok(root((1, 1), 3)));
+        assert_eq!(tan(pi_times((11, 6).into())), Ok(root((-1, 3), 3)));
+        assert_eq!(tan(pi_times((3, 4).into())), Ok(Data::Int(-1)));
+    }
+
+    #[test]
+    fn degrees_hit_the_lookup_tables() {
+        let degrees = AngleMode::Degrees;
+        assert_eq!(
+            sin(degrees.to_radians(Data::Int(30)).unwrap()),
+            Ok(Data::Rational((1, 2).into()))
+        );
+        assert_eq!(
+            degrees.from_radians(asin(Data::Rational((1, 2).into())).unwrap()),
+            Ok(Data::Int(30))
+        );
+        assert_eq!(AngleMode::Gradians.from_radians(pi_times((1, 2).into())), Ok(Data::Int(100)));
+        assert_eq!(cos(degrees.to_radians(Data::Int(180)).unwrap()), Ok(Data::Int(-1)));
+        assert_eq!(cos(degrees.to_radians(Data::Int(120)).unwrap()), Ok(Data::Rational((-1, 2).into())));
+        assert_eq!(sin(degrees.to_radians(Data::Int(-30)).unwrap()), Ok(Data::Rational((-1, 2).into())));
+        assert_eq!(tan(degrees.to_radians(Data::Int(-45)).unwrap()), Ok(Data::Int(-1)));
+    }
+
+    #[test]
+    fn degrees_past_the_first_quadrant() {
+        let degrees = AngleMode::Degrees;
+        assert_eq!(tan(degrees.to_radians(Data::Int(150)).unwrap()), Ok(root((-1, 3), 3)));
+        assert_eq!(sec(degrees.to_radians(Data::Int(30)).unwrap()), Ok(root((2, 3), 3)));
+        assert_eq!(csc(degrees.to_radians(Data::Int(45)).unwrap()), Ok(root((1, 1), 2)));
+        let undefined = tan(degrees.to_radians(Data::Int(90)).unwrap()).unwrap_err();
+        assert!(!undefined.message().contains('π'));
+    }
+
+    #[test]
+    fn unknown_angle_modes_say_what_the_modes_are() {
+        let unknown = "turns".parse::<AngleMode>().unwrap_err();
+        assert!(matches!(unknown, Error::InvalidArgument(_)));
+        assert!(unknown.message().contains("rad, deg and grad"));
+        assert_eq!("deg".parse(), Ok(AngleMode::Degrees));
+    }
+
+    #[test]
+    fn degrees_outside_the_lookup_tables() {
+        let degrees = AngleMode::Degrees;
+        let one = 1f64.to_radians();
+        assert_eq!(cos(degrees.to_radians(Data::Int(1)).unwrap()), Ok(Data::Float(one.cos())));
+        assert_eq!(tan(degrees.to_radians(Data::Int(1)).unwrap()), Ok(Data::Float(one.tan())));
+        assert_eq!(cos(Data::Float(1.)), Ok(Data::Float(1f64.cos())));
+    }
+
+    #[test]
+    fn inverses_fall_back_to_floats() {
+        assert_eq!(asin(Data::Rational((1, 3).into())), Ok(Data::Float((1. / 3f64).asin())));
+        assert!(acos(Data::Int(2)).is_err());
+    }
+}
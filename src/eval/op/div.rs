@@ -1,34 +1,56 @@
-use crate::eval::{op::pow::Pow, Data, DivisibleBy, Radical, SymbolEval, Symbolic};
+use crate::error::Error;
+use crate::eval::{
+    big::exact_arithmetic, complex::Complex, radical_sum::RadicalSum, Data, DivisibleBy, Polynomial, Radical, SymbolEval,
+    Symbolic,
+};
 use num::rational::Ratio;
+use num::traits::CheckedDiv;
 use std::convert::TryFrom;
 use std::ops::Div;
 
 impl Div for Data {
-    type Output = Result<Self, String>;
+    type Output = Result<Self, Error>;
     fn div(self, rhs: Self) -> Self::Output {
+        // a factorisation is just a number once there's arithmetic to do
+        if let (Self::Factorisation(_), _) | (_, Self::Factorisation(_)) = (&self, &rhs) {
+            return self.unfactorise() / rhs.unfactorise();
+        }
         if rhs == Self::Int(0) {
-            Err(String::from("Maths error: Divide by 0"))
+            Err(Error::domain("Divide by 0"))
         } else if rhs == Self::Int(1) {
             Ok(self)
+        } else if let Some(quotient) =
+            exact_arithmetic(&self, &rhs, |a, b| a.checked_div(b), |a, b| a / b)
+        {
+            // ints and rationals of any size, which are never 0 here
+            Ok(quotient)
+        } else if matches!(self, Self::Polynomial(_)) || matches!(rhs, Self::Polynomial(_)) {
+            Ok(Polynomial::from(self).div(Polynomial::from(rhs))?.into())
+        } else if matches!(self, Self::Complex(_)) || matches!(rhs, Self::Complex(_)) {
+            Ok(Complex::from(self).div(Complex::from(rhs))?.into())
+        } else if matches!(self, Self::Log(_)) || matches!(rhs, Self::Log(_)) {
+            match (self, rhs) {
+                // log_b(x) / log_b(y) = log_y(x)
+                (Self::Log(a), Self::Log(b)) => a.div(*b),
+                (Self::Log(a), k) if k.to_big_rational().is_some() => a.scale((Self::Int(1) / k)?),
+                (a, b) => Ok(Self::Float(f64::try_from(a)? / f64::try_from(b)?)),
+            }
+        } else if matches!(self, Self::RadicalSum(_)) || matches!(rhs, Self::RadicalSum(_)) {
+            // sums of radicals get their denominators rationalised
+            match RadicalSum::both(&self, &rhs) {
+                Some((lhs, rhs)) => lhs.div(rhs),
+                None => Ok(Self::Float(f64::try_from(self)? / f64::try_from(rhs)?)),
+            }
+        } else if let (true, Self::Radical(r)) = (self.to_big_rational().is_some(), &rhs) {
+            // k / (c ⁿ√a) = (k / (c a)) ⁿ√(a^(n - 1))
+            self * r.clone().reciprocal()?
         } else {
             match self {
                 Self::Int(n) => match rhs {
-                    Self::Int(m) => {
-                        if n.divisible_by(m) {
-                            Ok(Self::Int(n / m))
-                        } else {
-                            Ok(Self::Rational(Ratio::from((n, m))))
-                        }
-                    }
                     Self::Float(m) => Ok(Self::Float(n as f64 / m)),
                     Self::Symbol(m) => Ok(Self::Float(n as f64 / m.symbol_eval()?)),
                     Self::Symbolic(m) => Ok(Self::Float(n as f64 / m.as_float()?)),
-                    Self::Radical(r) => {
-                        (Self::Int(n) * Self::Radical(r.clone().conjugate()?))? / *r.radicand
-                    }
-                    Self::Rational(m) => {
-                        (Self::Int(n) * Self::Int(*m.denom()))? / Self::Int(*m.numer())
-                    }
+                    m => Ok(Self::Float(n as f64 / f64::try_from(m)?)),
                 },
                 Self::Symbol(s) => match rhs {
                     Self::Int(m) => Ok(Self::Symbolic(
@@ -106,87 +128,16 @@ impl Div for Data {
                     )),
                 },
                 Self::Radical(n) => match rhs {
-                    Self::Int(m) => Ok(Self::Radical(Radical::new(
-                        n.coefficient / m,
-                        n.index,
-                        n.radicand,
-                    ))),
-                    Self::Radical(m) => {
-                        if n.divisible_by(&m) {
-                            if n.index == m.index && n.radicand == m.radicand {
-                                Ok(Self::Radical(Radical::new(
-                                    n.coefficient / m.coefficient,
-                                    n.index,
-                                    n.radicand,
-                                )))
-                            } else if n.index.divisible_by(m.index) {
-                                let rhs_modified = Radical::new(
-                                    m.coefficient,
-                                    n.index,
-                                    m.radicand
-                                        .clone()
-                                        .pow(
-                                            (Data::from(n.index as i64)
-                                                / Data::from(m.index as i64))?,
-                                        )?
-                                        .into(),
-                                );
-                                if rhs_modified.radicand == n.radicand {
-                                    Ok(Self::Radical(Radical::new(
-                                        n.coefficient / rhs_modified.coefficient,
-                                        n.index,
-                                        n.radicand,
-                                    )))
-                                } else {
-                                    Self::Radical(n).as_float()? / Data::from(m.as_float()?)
-                                }
-                            } else if m.index.divisible_by(n.index) {
-                                let lhs_modified = Radical::new(
-                                    n.coefficient,
-                                    m.index,
-                                    n.radicand
-                                        .clone()
-                                        .pow(
-                                            (Data::from(m.index as i64)
-                                                / Data::from(n.index as i64))?,
-                                        )?
-                                        .into(),
-                                );
-                                if lhs_modified.radicand == n.radicand {
-                                    Ok(Self::Radical(Radical::new(
-                                        lhs_modified.coefficient / m.coefficient,
-                                        n.index,
-                                        n.radicand,
-                                    )))
-                                } else {
-                                    Self::Radical(n).as_float()? / Self::Radical(m).as_float()?
-                                }
-                            } else {
-                                Self::Radical(n).as_float()? / Self::Radical(m).as_float()?
-                            }
-                        } else {
-                            Self::Radical(n).as_float()? / Self::Radical(m).as_float()?
-                        }
-                    }
-                    Self::Rational(m) => Ok(Self::Radical(Radical::new(
-                        n.coefficient * m.recip(),
+                    // a / b = a (1 / b), and a radical's reciprocal has no root on the bottom
+                    Self::Radical(m) => Self::Radical(n) * m.reciprocal()?,
+                    k if k.to_big_rational().is_some() => Ok(Self::from(Radical::new(
+                        n.coefficient / k.to_big_rational().unwrap(),
                         n.index,
                         n.radicand,
                     ))),
                     b => Self::Radical(n).as_float()? / b.as_float()?,
                 },
-                Self::Rational(rat) => match rhs {
-                    Self::Int(_) => Self::Int(*rat.numer()) / (Self::Int(*rat.denom()) * rhs)?,
-                    Self::Rational(r) => {
-                        Self::Int(*rat.numer() * *r.denom()) / Self::Int(*rat.denom() * r.numer())
-                    }
-                    Self::Radical(r) => {
-                        Self::Int(*rat.numer())
-                            / ((*r.radicand * Self::Rational(r.coefficient))?
-                                * Self::Int(*rat.denom()))?
-                    }
-                    _ => self.as_float()? / rhs.as_float()?,
-                },
+                Self::Float(n) => Ok(Self::Float(n / f64::try_from(rhs)?)),
                 a => a.as_float()? / rhs.as_float()?,
             }
         }
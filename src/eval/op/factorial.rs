@@ -0,0 +1,140 @@
+/*! The postfix operators, `n!`, `n!!` and `x%` */
+
+use crate::error::Error;
+use crate::eval::Data;
+use num::bigint::BigInt;
+use num::traits::ToPrimitive;
+use std::convert::TryFrom;
+
+/// Anything past this would take ages to work out and pages to print
+pub const BIGGEST_EXACT_FACTORIAL: i64 = 10_000;
+
+pub trait Factorial {
+    type Output;
+    /// `n!`, exact for whole numbers and through the gamma function for anything else
+    fn factorial(self) -> Self::Output;
+    /// `n!! = n (n - 2) (n - 4) ...`, which only makes sense for whole numbers
+    fn double_factorial(self) -> Self::Output;
+}
+
+impl Factorial for Data {
+    type Output = Result<Data, Error>;
+    fn factorial(self) -> Self::Output {
+        match whole_number(&self)? {
+            Some(n) if n < 0 => Err(Error::domain(format!("{}! isn't defined for negative whole numbers", n))),
+            Some(n) => Ok(product_stepping_by(n, 1)),
+            None => {
+                let x = f64::try_from(self)?;
+                Ok(Data::Float(gamma(x + 1.)))
+            }
+        }
+    }
+
+    fn double_factorial(self) -> Self::Output {
+        match whole_number(&self)? {
+            // (-1)!! is 1, so that (n + 2)!! = (n + 2) n!! keeps working for n = 1
+            Some(n) if n < -1 => Err(Error::domain(format!("{}!! isn't defined, it's negative", n))),
+            Some(n) => Ok(product_stepping_by(n, 2)),
+            None => Err(Error::domain(format!(
+                "{}!! isn't defined, double factorials only work on whole numbers",
+                self
+            ))),
+        }
+    }
+}
+
+/// `x%`, which is just x hundredths
+pub fn percent(x: Data) -> Result<Data, Error> {
+    x / Data::Int(100)
+}
+
+/// `Some(n)` if `x` is the whole number n, `None` if it's some other number,
+/// and an error if it's a whole number too big to take the factorial of exactly
+fn whole_number(x: &Data) -> Result<Option<i64>, Error> {
+    let n = match x {
+        Data::Int(n) => *n,
+        Data::BigInt(n) => n.to_i64().unwrap_or(i64::MAX),
+        Data::Float(f) if f.fract() == 0. && f.abs() <= BIGGEST_EXACT_FACTORIAL as f64 => *f as i64,
+        _ => return Ok(None),
+    };
+    if n > BIGGEST_EXACT_FACTORIAL {
+        Err(Error::Overflow(format!(
+            "{}! is too big, only factorials up to {}! can be worked out",
+            x, BIGGEST_EXACT_FACTORIAL
+        )))
+    } else {
+        Ok(Some(n))
+    }
+}
+
+/// `n (n - step) (n - 2 step) ...` down to 1, in big integers so that it never overflows
+fn product_stepping_by(n: i64, step: usize) -> Data {
+    (1..=n.max(1))
+        .rev()
+        .step_by(step)
+        .map(BigInt::from)
+        .product::<BigInt>()
+        .into()
+}
+
+/// The gamma function, which is the factorial shifted by one, `gamma(n + 1) = n!`, but for any number.
+/// This is the Lanczos approximation, with the reflection formula for everything left of 1/2
+fn gamma(x: f64) -> f64 {
+    const G: f64 = 7.;
+    const COEFFICIENTS: [f64; 9] = [
+        0.999_999_999_999_809_9,
+        676.520_368_121_885_1,
+        -1_259.139_216_722_402_8,
+        771.323_428_777_653_1,
+        -176.615_029_162_140_6,
+        12.507_343_278_686_905,
+        -0.138_571_095_265_720_12,
+        9.984_369_578_019_572e-6,
+        1.505_632_735_149_311_6e-7,
+    ];
+    if x < 0.5 {
+        std::f64::consts::PI / ((std::f64::consts::PI * x).sin() * gamma(1. - x))
+    } else {
+        let x = x - 1.;
+        let t = x + G + 0.5;
+        let series = COEFFICIENTS[1..]
+            .iter()
+            .enumerate()
+            .fold(COEFFICIENTS[0], |sum, (i, c)| sum + c / (x + i as f64 + 1.));
+        (2. * std::f64::consts::PI).sqrt() * t.powf(x + 0.5) * (-t).exp() * series
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn whole_factorials_are_exact() {
+        assert_eq!(Data::Int(0).factorial(), Ok(Data::Int(1)));
+        assert_eq!(Data::Int(5).factorial(), Ok(Data::Int(120)));
+        assert_eq!(
+            format!("{}", Data::Int(25).factorial().unwrap()),
+            "15511210043330985984000000"
+        );
+        assert!(Data::Int(-3).factorial().is_err());
+        assert!(matches!(Data::Int(1_000_000).factorial(), Err(Error::Overflow(_))));
+    }
+
+    #[test]
+    fn double_factorials() {
+        assert_eq!(Data::Int(7).double_factorial(), Ok(Data::Int(105)));
+        assert_eq!(Data::Int(8).double_factorial(), Ok(Data::Int(384)));
+        assert_eq!(Data::Int(-1).double_factorial(), Ok(Data::Int(1)));
+        assert!(Data::Float(2.5).double_factorial().is_err());
+    }
+
+    #[test]
+    fn everything_else_goes_through_gamma() {
+        // (1/2)! is √π / 2
+        let half = f64::try_from(Data::Rational((1, 2).into()).factorial().unwrap()).unwrap();
+        assert!((half - std::f64::consts::PI.sqrt() / 2.).abs() < 1e-12);
+        let minus_half = f64::try_from(Data::Float(-0.5).factorial().unwrap()).unwrap();
+        assert!((minus_half - std::f64::consts::PI.sqrt()).abs() < 1e-12);
+    }
+}
@@ -5,6 +5,7 @@ pub mod div;
 pub mod pow;
 pub mod neg;
 pub mod root;
+pub mod factorial;
 pub mod calculate_fn;
 
 
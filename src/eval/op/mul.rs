@@ -1,34 +1,48 @@
-use crate::eval::{op::pow::Pow, Data, DivisibleBy, Radical, Symbolic};
+use crate::error::Error;
+use crate::eval::{big::exact_arithmetic, complex::Complex, op::pow::Pow, radical_sum::RadicalSum, Data, DivisibleBy, Polynomial, Radical, Symbolic};
 use crate::util::option::{Catch, OrMerge};
+use num::integer::lcm;
+use num::traits::CheckedMul;
 use std::convert::TryFrom;
 use std::ops::Mul;
 
 impl Mul for Data {
-    type Output = Result<Data, String>;
+    type Output = Result<Data, Error>;
     fn mul(self, rhs: Self) -> Self::Output {
+        // a factorisation is just a number once there's arithmetic to do
+        if let (Self::Factorisation(_), _) | (_, Self::Factorisation(_)) = (&self, &rhs) {
+            return self.unfactorise() * rhs.unfactorise();
+        }
+        // Ints and Rationals, promoted if they overflow. This also makes sure a Rational with denominator 1 comes out as an Int
+        if let Some(product) = exact_arithmetic(&self, &rhs, |a, b| a.checked_mul(b), |a, b| a * b) {
+            return Ok(product);
+        }
+        // sums of radicals get multiplied out term by term
+        if matches!(self, Self::RadicalSum(_)) || matches!(rhs, Self::RadicalSum(_)) {
+            if let Some((lhs, rhs)) = RadicalSum::both(&self, &rhs) {
+                return lhs.mul(rhs);
+            }
+        }
         match (self, rhs) {
+            // anything times a polynomial gets multiplied into every term
+            (Self::Polynomial(p), a) | (a, Self::Polynomial(p)) => {
+                Ok(Polynomial::from(a).mul(&p)?.into())
+            }
+            (Self::Complex(z), a) | (a, Self::Complex(z)) => Ok(Complex::from(a).mul(*z)?.into()),
+            // k log(x) = log(x^k)
+            (Self::Log(l), k) | (k, Self::Log(l)) if k.to_big_rational().is_some() => l.scale(k),
             // Easy ones first: same type so we get commutation free
-            (Self::Int(a), Self::Int(b)) => Ok(Self::Int(a * b)),
             (Self::Float(a), Self::Float(b)) => Ok(Self::Float(a * b)),
-            // We can't rely entirely on the implementation that num provides, because when the denominator = 1 it needs to be an Int
-            (Self::Rational(a), Self::Rational(b)) => {
-                let result = a * b;
-                if *result.denom() == 1 {
-                    Ok(Self::Int(*result.numer()))
-                } else {
-                    Ok(Self::Rational(result))
-                }
-            }
             // Ok now we have to implement this ourself
             (Self::Radical(a), Self::Radical(b)) => {
                 Ok(if a.index == b.index {
-                    Self::Radical(Radical::new(
+                    Self::from(Radical::new(
                         a.coefficient * b.coefficient,
                         a.index,
                         Box::new((*a.radicand * *b.radicand)?),
                     ))
                 } else if a.index.divisible_by(b.index) {
-                    Self::Radical(Radical::new(
+                    Self::from(Radical::new(
                         a.coefficient * b.coefficient,
                         a.index,
                         Box::new(
@@ -38,7 +52,7 @@ impl Mul for Data {
                         ),
                     ))
                 } else if b.index.divisible_by(a.index) {
-                    Self::Radical(Radical::new(
+                    Self::from(Radical::new(
                         b.coefficient * a.coefficient,
                         b.index,
                         Box::new(
@@ -47,16 +61,17 @@ impl Mul for Data {
                                     .pow(Data::from(b.index as i64 / a.index as i64))?)?,
                         ),
                     ))
-                } else if *a.radicand == *b.radicand {
-                    Self::Radical(Radical::new(
-                        b.coefficient * a.coefficient,
-                        a.index + b.index,
-                        a.radicand,
+                } else {
+                    // ᵐ√a ⁿ√b = ᵏ√(a^(k/m) b^(k/n)), where k is the lowest common multiple of m and n
+                    let k = lcm(a.index, b.index);
+                    Self::from(Radical::new(
+                        a.coefficient * b.coefficient,
+                        k,
+                        Box::new(
+                            (a.radicand.pow(Data::from((k / a.index) as i64))?
+                                * b.radicand.pow(Data::from((k / b.index) as i64))?)?,
+                        ),
                     ))
-                }
-                // I can't think of any further improvements so I guess we just go
-                else {
-                    Self::Float(a.as_float()? * b.as_float()?)
                 })
             }
             (Self::Symbol(a), Self::Symbol(b)) => Ok(Self::Symbolic(
@@ -214,23 +229,13 @@ impl Mul for Data {
                     .into(),
                 ))
             }
-            (Self::Int(int), Self::Rational(rat)) | (Self::Rational(rat), Self::Int(int)) => {
-                let result = rat * int;
-                if *result.denom() == 1 {
-                    Ok(Self::Int(*result.numer()))
-                } else {
-                    Ok(Self::Rational(result))
-                }
+            (k, Self::Radical(rad)) | (Self::Radical(rad), k) if k.to_big_rational().is_some() => {
+                Ok(Self::from(Radical::new(
+                    rad.coefficient * k.to_big_rational().unwrap(),
+                    rad.index,
+                    rad.radicand,
+                )))
             }
-            (Self::Int(int), Self::Radical(rad)) | (Self::Radical(rad), Self::Int(int)) => Ok(
-                Self::Radical(Radical::new(rad.coefficient * int, rad.index, rad.radicand)),
-            ),
-            (Self::Rational(rat), Self::Radical(rad))
-            | (Self::Radical(rad), Self::Rational(rat)) => Ok(Self::Radical(Radical::new(
-                rad.coefficient * rat,
-                rad.index,
-                rad.radicand,
-            ))),
             (a, Self::Symbol(s)) | (Self::Symbol(s), a) => Ok(Self::Symbolic(
                 Symbolic {
                     coeff: Some(a),
@@ -239,6 +244,18 @@ impl Mul for Data {
                 }
                 .into(),
             )),
+            (Self::Symbolic(syc), big) | (big, Self::Symbolic(syc)) => Ok(Self::Symbolic(
+                Symbolic {
+                    coeff: syc.coeff.or_merge(|x, y| x * y, Ok(Some(big.clone())))?,
+                    symbol: syc.symbol,
+                    constant: match syc.constant.map(|x| x * big) {
+                        None => None,
+                        Some(t) => Some(t?),
+                    },
+                }
+                .into(),
+            )),
+            (a, b) => Ok(Self::Float(f64::try_from(a)? * f64::try_from(b)?)),
         }
     }
 }
@@ -1,11 +1,17 @@
-use crate::eval::{Data, Radical, Symbolic};
+use crate::eval::{primes::Factorisation, Data, Radical, Symbolic};
+use num::bigint::BigInt;
 use std::ops::Neg;
 
 impl Neg for Data {
     type Output = Self;
     fn neg(self) -> Self::Output {
         match self {
-            Self::Int(i) => Self::Int(-i),
+            Self::Int(i) => match i.checked_neg() {
+                Some(n) => Self::Int(n),
+                None => Self::from(-BigInt::from(i)),
+            },
+            Self::BigInt(i) => Self::from(-i),
+            Self::BigRational(r) => Self::from(-r),
             Self::Float(f) => Self::Float(-f),
             Self::Symbol(s) => Self::Symbolic(Box::new(Symbolic {
                 coeff: Some(Self::Int(-1)),
@@ -18,7 +24,15 @@ impl Neg for Data {
                 constant: s.constant.map(|x| -x)
             })),
             Self::Rational(r) => Self::Rational(-r),
-            Self::Radical(r) => Self::Radical(Radical::new( -r.coefficient, r.index, r.radicand))
+            Self::Radical(r) => Self::from(Radical::new( -r.coefficient, r.index, r.radicand)),
+            Self::Polynomial(p) => p.neg().into(),
+            Self::Complex(z) => z.neg().into(),
+            Self::RadicalSum(s) => s.neg().into(),
+            Self::Log(l) => Self::Log(Box::new(l.neg())),
+            Self::Factorisation(f) => Self::Factorisation(Factorisation {
+                negative: !f.negative,
+                ..f
+            }),
         }
     }
 }
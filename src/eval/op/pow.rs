@@ -1,5 +1,8 @@
+use crate::error::Error;
 use crate::eval::{op::root::NthRoot, Data, DivisibleBy, SymbolEval, Symbolic};
-use std::convert::TryInto;
+use num::bigint::BigInt;
+use num::traits::Zero;
+use std::convert::{TryFrom, TryInto};
 
 pub trait Pow<RHS = Self> {
     type Output;
@@ -8,14 +11,24 @@ pub trait Pow<RHS = Self> {
 }
 
 impl Pow for Data {
-    type Output = Result<Self, String>;
+    type Output = Result<Self, Error>;
 
     fn pow(self, rhs: Self) -> Self::Output {
         let invert_result = rhs < Data::from(0);
-        let abs_rhs = if invert_result { -rhs } else { rhs };
+        let abs_rhs = if invert_result { -rhs } else { rhs }.unfactorise();
+        // 0, 1, -1 and i go round in circles, so they have exact powers however big the exponent is
+        if let Some(k) = whole_number(&abs_rhs).and_then(|j| power_of_unit(&self, &j)) {
+            return if invert_result { Data::Int(1) / k } else { Ok(k) };
+        }
         match self {
             Self::Int(i) => match abs_rhs {
-                Self::Int(j) => Ok(Data::Int(i.pow(j as u32))),
+                Self::Int(j) => {
+                    let j = exact_power_of(&BigInt::from(i), j)?;
+                    Ok(match i.checked_pow(j) {
+                        Some(n) => Data::Int(n),
+                        None => Data::from(BigInt::from(i).pow(j)),
+                    })
+                }
                 Self::Float(f) => Ok(Data::Float((i as f64).powf(f))),
                 Self::Radical(r) => Ok(Data::Float((i as f64).powf(r.as_float()?))),
                 Self::Rational(r) => self
@@ -23,14 +36,33 @@ impl Pow for Data {
                     .and_then(|x| x.nth_root(*r.denom())),
                 Self::Symbol(s) => Ok(Self::Float((i as f64).powf(s.symbol_eval()?))),
                 Self::Symbolic(s) => Ok(Self::Float((i as f64).powf(s.as_float()?))),
+                Self::BigInt(j) => Err(too_big(&BigInt::from(i), &j)),
+                big => Ok(Self::Float((i as f64).powf(f64::try_from(big)?))),
+            },
+            Self::BigInt(i) => match abs_rhs {
+                Self::Int(j) => {
+                    let j = exact_power_of(&i, j)?;
+                    Ok(Data::from(i.pow(j)))
+                }
+                Self::Rational(r) => Self::BigInt(i)
+                    .pow(Self::Int(*r.numer()))
+                    .and_then(|x| x.nth_root(*r.denom())),
+                Self::BigInt(j) => Err(too_big(&i, &j)),
+                a => Ok(Self::Float(
+                    f64::try_from(Self::BigInt(i))?.powf(f64::try_from(a)?),
+                )),
             },
+            Self::BigRational(i) => {
+                Data::from(i.numer().clone()).pow(abs_rhs.clone())?
+                    / Data::from(i.denom().clone()).pow(abs_rhs)?
+            }
             Self::Float(i) => Ok(Self::Float(i.powf(abs_rhs.try_into()?))),
             Self::Rational(i) => {
                 Self::Int(*i.numer()).pow(abs_rhs.clone())? / Self::Int(*i.denom()).pow(abs_rhs)?
             }
             Self::Radical(i) => match abs_rhs {
                 Self::Int(j) if j.divisible_by(i.index as i64) => {
-                    Self::Rational(i.coefficient).pow(Self::Int(j))?
+                    Self::from(i.coefficient).pow(Self::Int(j))?
                         * i.radicand.pow(Self::Int(j / i.index as i64))?
                 }
                 Self::Rational(j) => match Self::Radical(i).pow(Self::Int(*j.numer())) {
@@ -43,7 +75,15 @@ impl Pow for Data {
                 a => Ok(Data::Float(i.as_float()?.powf(a.try_into()?))),
             },
             Self::Symbol(i) => match abs_rhs {
-                Self::Int(j) => Self::Symbol(i).naive_pow(j as u32),
+                Self::Int(0) => Ok(Self::Int(1)),
+                Self::Int(j) => {
+                    let j = exact_exponent(j)?;
+                    if j as u64 > BIGGEST_EXACT_POWER_BITS {
+                        return Err(Error::Overflow(format!("the exponent {} is too large", j)));
+                    }
+                    Self::Symbol(i).naive_pow(j)
+                }
+                Self::BigInt(j) => Err(Error::Overflow(format!("the exponent {} is too large", j))),
                 Self::Rational(j) => Self::Symbol(i)
                     .pow(Self::Int(*j.numer()))
                     .and_then(|x| x.nth_root(*j.denom())),
@@ -63,6 +103,7 @@ impl Pow for Data {
                     .symbol_eval()
                     .and_then(|x| Ok(x.powf(j.as_float()?)))
                     .map(|d| Data::from(d)),
+                big => Ok(Data::from(i.symbol_eval()?.powf(f64::try_from(big)?))),
             },
             Self::Symbolic(i) => {
                 if i.constant == None {
@@ -89,6 +130,31 @@ impl Pow for Data {
                     Self::Symbolic(i).as_float()?.pow(abs_rhs.as_float()?)
                 }
             }
+            Self::Complex(z) => match abs_rhs {
+                Self::Int(j) => Ok(z.pow(exact_exponent(j)?)?.into()),
+                Self::Rational(j) => Self::Complex(z)
+                    .pow(Self::Int(*j.numer()))
+                    .and_then(|x| x.nth_root(*j.denom())),
+                a => Ok(z.powf(f64::try_from(a)?)?.into()),
+            },
+            Self::Factorisation(f) => Self::from(f.value()).pow(abs_rhs),
+            Self::Log(l) => Ok(Self::Float(l.as_float()?.powf(f64::try_from(abs_rhs)?))),
+            Self::RadicalSum(s) => match abs_rhs {
+                Self::Int(0) => Ok(Self::Int(1)),
+                Self::Int(j) => Self::RadicalSum(s).naive_pow(exact_exponent(j)?),
+                Self::Rational(j) => Self::RadicalSum(s)
+                    .pow(Self::Int(*j.numer()))
+                    .and_then(|x| x.nth_root(*j.denom())),
+                a => Ok(Self::Float(s.as_float()?.powf(f64::try_from(a)?))),
+            },
+            Self::Polynomial(p) => match abs_rhs {
+                Self::Int(j) => Ok(p.pow(exact_exponent(j)?)?.into()),
+                a => Err(Error::algebra(format!(
+                    "can't raise {} to the power of {}, only to whole numbers",
+                    Self::Polynomial(p),
+                    a
+                ))),
+            },
         }
         .and_then(|k| {
             if invert_result {
@@ -100,6 +166,53 @@ impl Pow for Data {
     }
 }
 
+/// Anything past this many bits would take ages to work out and pages to print
+const BIGGEST_EXACT_POWER_BITS: u64 = 1 << 20;
+
+/// Exact powers need the exponent to fit in a `u32`, anything bigger would never fit in memory anyway
+fn exact_exponent(j: i64) -> Result<u32, Error> {
+    u32::try_from(j).map_err(|_| Error::Overflow(format!("the exponent {} is too large", j)))
+}
+
+/// The exponent for `base^j`, as long as the answer isn't too big to work out exactly,
+/// which it is when `log2(base) j`, about how many bits it would take, gets past `BIGGEST_EXACT_POWER_BITS`
+fn exact_power_of(base: &BigInt, j: i64) -> Result<u32, Error> {
+    let j = exact_exponent(j)?;
+    if base.bits().saturating_sub(1).saturating_mul(j as u64) > BIGGEST_EXACT_POWER_BITS {
+        Err(too_big(base, &BigInt::from(j)))
+    } else {
+        Ok(j)
+    }
+}
+
+fn too_big(base: &BigInt, j: &BigInt) -> Error {
+    Error::Overflow(format!("{}^{} is too big to work out exactly", base, j))
+}
+
+/// The exponent, if it's a whole number, however big
+fn whole_number(data: &Data) -> Option<BigInt> {
+    match data {
+        Data::Int(j) => Some(BigInt::from(*j)),
+        Data::BigInt(j) => Some(j.clone()),
+        _ => None,
+    }
+}
+
+/// `base^j` for the bases whose powers repeat, 0, 1 and -1, and i, which repeats every 4,
+/// so only `j` mod 4 matters
+fn power_of_unit(base: &Data, j: &BigInt) -> Option<Data> {
+    let cycle = u32::try_from(j % 4).ok()?;
+    match base {
+        Data::Int(0) if j.is_zero() => Some(Data::Int(1)),
+        Data::Int(0) => Some(Data::Int(0)),
+        Data::Int(b) if b.abs() == 1 => Some(Data::Int(b.pow(cycle))),
+        Data::Complex(z) if z.re == Data::Int(0) && matches!(z.im, Data::Int(1) | Data::Int(-1)) => {
+            z.as_ref().clone().pow(cycle).ok().map(Data::from)
+        }
+        _ => None,
+    }
+}
+
 trait NaivePow {
     type Output;
     fn naive_pow(self, pow: u32) -> Self::Output;
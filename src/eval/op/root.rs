@@ -1,5 +1,15 @@
-use crate::eval::{Data, DivisibleBy, Radical};
-use num::rational::Ratio;
+use crate::error::Error;
+use crate::eval::{
+    complex::Complex,
+    op::{
+        calculate_fn::{cos, sin},
+        pow::Pow,
+    },
+    Data, DivisibleBy, Radical,
+};
+use num::bigint::BigInt;
+use num::rational::BigRational;
+use num::traits::{One, Signed};
 use std::convert::TryInto;
 pub trait NthRoot<RHS = Self>
 where
@@ -119,134 +129,139 @@ impl NthRoot for u32 {
 }
 
 impl NthRoot<i64> for Data {
-    type Output = Result<Self, String>;
+    type Output = Result<Self, Error>;
     fn nth_root(self, rhs: i64) -> Self::Output {
         if rhs == 0 {
-            Err(String::from(
-                "Maths error: cannot take the 0th root of a number",
-            ))
+            Err(Error::domain("cannot take the 0th root of a number"))
+        } else if rhs.divisible_by(2) && self < Self::Int(0) {
+            // even roots of negatives are complex, ⁿ√(-a) = ⁿ√a (cos(π/n) + i sin(π/n)),
+            // which is exact for the angles trig knows, so √(-4) = 2i and ∜(-16) = √2 + √2i
+            let magnitude = (-self).nth_root(rhs.abs())?;
+            let angle = (Self::Symbol("pi".into()) / Self::Int(rhs.abs()))?;
+            let root = (magnitude * Self::from(Complex::new(cos(angle.clone())?, sin(angle)?)))?;
+            if rhs < 0 {
+                Self::Int(1) / root
+            } else {
+                Ok(root)
+            }
         } else {
-            Ok({
-                let (should_invert, index) = (rhs < 0, rhs.abs() as u32);
-                let mut should_negate = false;
-                match self {
-                    Self::Int(lhs) => {
-                        // If it is an int then 1:
-                        if lhs < 0 {
-                            // we need to check that we're not taking the square/4th etc root of a negative number
-                            if rhs.divisible_by(2) {
-                                return Err("Non-real error: even root of a negative number".into());
-                            } else {
-                                should_negate = true; // in that case we just negate the output of it as if it were a positive number
-                            }
-                        }
-                        let abs_lhs = lhs.abs();
-                        match (abs_lhs as u32).nth_root(index) {
-                            // now that that's sorted out, does the root resolve to an integer?
-                            Some(n) => Self::Int(n as i64 * if should_negate { -1 } else { 1 }), // if so, return that (negated as necessary)
-                            None => Self::Radical(Radical::new_raw(
-                                if should_invert {
-                                    Ratio::from(-1)
-                                } else {
-                                    Ratio::from(1)
-                                },
-                                index,
-                                Box::new(self),
-                            )),
-                        }
+            let (should_invert, index) = (rhs < 0, rhs.abs() as u32);
+            let mut should_negate = false;
+            let root = match self {
+                Self::Int(_) | Self::Rational(_) => {
+                    if self < Self::Int(0) && rhs.divisible_by(2) {
+                        // we need to check that we're not taking the square/4th etc root of a negative number
+                        return Err(Error::non_real("even root of a negative number"));
                     }
-                    Self::Float(n) => {
-                        if n < 0. {
-                            // we need to check that we're not taking the square/4th etc root of a negative number
-                            if rhs.divisible_by(2) {
-                                return Err("Non-real error: even root of a negative number".into());
-                            } else {
-                                should_negate = true; // in that case we just negate the output of it as if it were a positive number
-                            }
-                        }
-                        Self::Radical(Radical::new_raw(
-                            if should_negate {
-                                Ratio::from(-1)
-                            } else {
-                                Ratio::from(1)
-                            },
-                            index,
-                            Box::new(self),
-                        ))
+                    // simplifying takes out every perfect power, and the sign of odd roots of negatives
+                    Self::from(Radical::new(BigRational::one(), index, Box::new(self)))
+                }
+                Self::BigInt(n) => {
+                    if n.is_negative() && rhs.divisible_by(2) {
+                        return Err(Error::non_real("even root of a negative number"));
                     }
-                    Self::Rational(rat) => {
-                        if rat < Ratio::from(0) {
-                            // we need to check that we're not taking the square/4th etc root of a negative number
-                            if rhs.divisible_by(2) {
-                                return Err(
-                                    "Non-real error: even root of a negative number".to_string()
-                                );
-                            } else {
-                                should_negate = true; // in that case we just negate the output of it as if it were a positive number
-                            }
-                        }
-                        Self::Radical(Radical::new_raw(
-                            Ratio::from((if should_negate { 1 } else { -1 }, *rat.denom())),
-                            index,
-                            Box::new(Self::Int(*rat.denom() * *rat.numer())),
-                        ))
+                    let root = n.nth_root(index);
+                    if root.pow(index) == n {
+                        Self::from(root)
+                    } else {
+                        Self::from(Radical::new(BigRational::one(), index, Box::new(Self::BigInt(n))))
                     }
-                    Self::Radical(rad) => {
-                        let result =
-                            Radical::new_raw(rad.coefficient, rad.index * index, rad.radicand);
-                        if result.index.divisible_by(2) {
-                            if *result.radicand < Self::Int(0) {
-                                return Err(
-                                    "Non-real error: even root of a negative number".to_string()
-                                );
-                            } else {
-                                should_negate = true;
-                            }
-                        }
-                        if should_negate {
-                            (Self::Radical(result) * Self::Int(-1))?
+                }
+                Self::BigRational(r) => {
+                    // √(a/b) = √(a) / √(b), which will be exact if both of them are
+                    (Self::from(r.numer().clone()).nth_root(index as i64)?
+                        / Self::from(r.denom().clone()).nth_root(index as i64)?)?
+                }
+                // floats are already inexact, so there's no point keeping their roots as radicals
+                Self::Float(n) => Self::Float(
+                    n.nth_root(index as i64)
+                        .ok_or_else(|| Error::non_real("even root of a negative number"))?,
+                ),
+                Self::Radical(rad) => {
+                    // ᵐ√(c ⁿ√r) = ᵐⁿ√(cⁿ r), and a negative coefficient can only get here with an odd m,
+                    // so its sign comes straight out the front
+                    let negative = rad.coefficient.is_negative();
+                    let coefficient = Self::from(rad.coefficient.abs()).pow(Self::from(rad.index as i64))?;
+                    let radicand = (coefficient * *rad.radicand)?;
+                    let root = Self::from(Radical::new(BigRational::one(), rad.index * index, Box::new(radicand)));
+                    if negative {
+                        -root
+                    } else {
+                        root
+                    }
+                }
+                Self::Symbol(s) => {
+                    let f: f64 = Self::Symbol(s.clone()).try_into()?;
+                    if f < 0. {
+                        // we need to check that we're not taking the square/4th etc root of a negative number
+                        if rhs.divisible_by(2) {
+                            return Err(Error::non_real("even root of a negative number"));
                         } else {
-                            Self::Radical(result)
+                            should_negate = true; // in that case we just negate the output of it as if it were a positive number
                         }
                     }
-                    Self::Symbol(s) => {
-                        let f: f64 = Self::Symbol(s.clone()).try_into()?;
-                        if f < 0. {
-                            // we need to check that we're not taking the square/4th etc root of a negative number
-                            if rhs.divisible_by(2) {
-                                return Err(
-                                    "Non-real error: even root of a negative number".to_string()
-                                );
-                            } else {
-                                should_negate = true; // in that case we just negate the output of it as if it were a positive number
-                            }
+                    Self::Radical(Radical::new_raw(
+                        BigRational::from(BigInt::from(if should_negate { -1 } else { 1 })),
+                        index,
+                        Box::new(Self::Symbol(s)),
+                    ))
+                }
+                Self::Symbolic(s) => {
+                    let f: f64 = Self::Symbolic(s.clone()).try_into()?;
+                    if f < 0. {
+                        // we need to check that we're not taking the square/4th etc root of a negative number
+                        if rhs.divisible_by(2) {
+                            return Err(Error::non_real("even root of a negative number"));
+                        } else {
+                            should_negate = true; // in that case we just negate the output of it as if it were a positive number
                         }
-                        Self::Radical(Radical::new_raw(
-                            Ratio::from(if should_negate { 1 } else { -1 }),
-                            index,
-                            Box::new(Self::Symbol(s)),
-                        ))
                     }
-                    Self::Symbolic(s) => {
-                        let f: f64 = Self::Symbolic(s.clone()).try_into()?;
-                        if f < 0. {
-                            // we need to check that we're not taking the square/4th etc root of a negative number
-                            if rhs.divisible_by(2) {
-                                return Err(
-                                    "Non-real error: even root of a negative number".to_string()
-                                );
-                            } else {
-                                should_negate = true; // in that case we just negate the output of it as if it were a positive number
-                            }
-                        }
-                        Self::Radical(Radical::new_raw(
-                            Ratio::from(if should_negate { 1 } else { -1 }),
+                    Self::Radical(Radical::new_raw(
+                        BigRational::from(BigInt::from(if should_negate { -1 } else { 1 })),
+                        index,
+                        Box::new(Self::Symbolic(s)),
+                    ))
+                }
+                // square roots are exact, so even roots are square roots of square roots first
+                Self::Complex(z)
+                    if index.is_multiple_of(2) && !matches!((&z.re, &z.im), (Self::Float(_), _) | (_, Self::Float(_))) =>
+                {
+                    let root = Self::from(z.sqrt()?);
+                    if index == 2 {
+                        root
+                    } else {
+                        root.nth_root(index as i64 / 2)?
+                    }
+                }
+                Self::Complex(z) => Self::from(z.powf(1. / index as f64)?),
+                Self::Factorisation(f) => return Self::from(f.value()).nth_root(rhs),
+                Self::Log(l) => return Self::Float(l.as_float()?).nth_root(rhs),
+                Self::RadicalSum(s) => {
+                    let denested = if index == 2 { s.denest() } else { None };
+                    match denested {
+                        Some(root) => root,
+                        // odd roots of negatives are negative
+                        None if Self::RadicalSum(s.clone()) < Self::Int(0) => Self::Radical(Radical::new_raw(
+                            -BigRational::one(),
                             index,
-                            Box::new(Self::Symbolic(s)),
-                        ))
+                            Box::new(Self::from(s.neg())),
+                        )),
+                        None => Self::Radical(Radical::new_raw(BigRational::one(), index, Box::new(Self::RadicalSum(s)))),
                     }
                 }
-            })
+                Self::Polynomial(p) => {
+                    return Err(Error::algebra(format!(
+                        "can't take roots of {}",
+                        Self::Polynomial(p)
+                    )))
+                }
+            };
+            // the root of the reciprocal, ⁿ√(1/a) = 1/ⁿ√a, is the same in every case
+            if should_invert {
+                Self::Int(1) / root
+            } else {
+                Ok(root)
+            }
         }
     }
 }
@@ -254,6 +269,46 @@ impl NthRoot<i64> for Data {
 #[cfg(test)]
 mod test {
     use super::*;
+    use num::rational::Ratio;
+
+    #[test]
+    fn roots_of_fractions_and_constants_are_positive() {
+        let pi = Data::Symbol("pi".into());
+        let two_pi = (Data::Int(2) * pi.clone()).unwrap();
+        for x in vec![Data::Rational(Ratio::new(1, 2)), pi, two_pi] {
+            let root: f64 = x.clone().nth_root(2).unwrap().try_into().unwrap();
+            let expected: f64 = x.try_into().unwrap();
+            assert!((root - expected.sqrt()).abs() < 1e-9);
+        }
+    }
+
+    #[test]
+    fn roots_of_radicals() {
+        let root = |x: i64, n: i64| Data::Int(x).nth_root(n).unwrap();
+        assert_eq!(root(2, 2).nth_root(2).unwrap(), root(2, 4));
+        assert_eq!(root(2, 2).nth_root(3).unwrap(), root(2, 6));
+        // √(2√3) = ∜12
+        let two_root_three = (Data::Int(2) * root(3, 2)).unwrap();
+        assert_eq!(two_root_three.nth_root(2).unwrap(), root(12, 4));
+        assert_eq!(root(2, 2).nth_root(-2).unwrap(), (Data::Int(1) / root(2, 4)).unwrap());
+    }
+
+    #[test]
+    fn negative_roots_of_big_numbers() {
+        let big = Data::Int(2).pow(Data::Int(70)).unwrap();
+        let one_over = (Data::Int(1) / Data::Int(2).pow(Data::Int(35)).unwrap()).unwrap();
+        assert_eq!(big.clone().nth_root(-2).unwrap(), one_over);
+        let big_fraction = (big / Data::Int(3).pow(Data::Int(50)).unwrap()).unwrap();
+        let root = (Data::Int(3).pow(Data::Int(25)).unwrap() / Data::Int(2).pow(Data::Int(35)).unwrap()).unwrap();
+        assert_eq!(big_fraction.nth_root(-2).unwrap(), root);
+    }
+
+    #[test]
+    fn roots_of_floats_are_floats() {
+        assert_eq!(Data::Float(8.).nth_root(3).unwrap(), Data::Float(2.));
+        assert_eq!(Data::Float(-8.).nth_root(3).unwrap(), Data::Float(-2.));
+        assert_eq!(Data::Float(4.).nth_root(-2).unwrap(), Data::Float(0.5));
+    }
 
     #[test]
     fn pascals_triangle() {
@@ -1,9 +1,10 @@
+use crate::error::Error;
 use crate::eval::Data;
 use std::ops::Sub;
 
 //possibly a little simplistic but alas
 impl Sub for Data {
-    type Output = Result<Self, String>;
+    type Output = Result<Self, Error>;
     fn sub(self, rhs: Self) -> Self::Output {
         self + -rhs
     }
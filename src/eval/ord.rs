@@ -1,26 +1,51 @@
-use crate::eval::{op::pow::Pow, ratio_as_float, Data, SymbolEval, Symbolic};
+use crate::eval::{big::big, op::pow::Pow, ratio_as_float, Data, SymbolEval, Symbolic};
+use num::bigint::BigInt;
 use num::integer::lcm;
-use num::rational::Ratio;
+use num::rational::{BigRational, Ratio};
+use num::traits::Signed;
 use std::cmp::Ordering;
+use std::convert::TryFrom;
 
 impl std::cmp::PartialOrd for Data {
     fn partial_cmp(&self, other: &Self) -> Option<std::cmp::Ordering> {
+        if self.is_big() || other.is_big() {
+            return match (self.to_big_rational(), other.to_big_rational()) {
+                (Some(a), Some(b)) => a.partial_cmp(&b),
+                // big numbers against anything inexact might as well be floats
+                _ => f64::try_from(self.clone())
+                    .ok()?
+                    .partial_cmp(&f64::try_from(other.clone()).ok()?),
+            };
+        }
         match (&self, &other) {
+            // free variables could be anything, so there's no telling which is bigger
+            (Self::Polynomial(a), Self::Polynomial(b)) if a == b => Some(Ordering::Equal),
+            (Self::Polynomial(_), _) | (_, Self::Polynomial(_)) => None,
+            // nor is there for complex numbers
+            (Self::Complex(a), Self::Complex(b)) if a == b => Some(Ordering::Equal),
+            (Self::Complex(_), _) | (_, Self::Complex(_)) => None,
+            // sums of radicals are only equal when their difference is exactly 0, otherwise a float can tell them apart
+            // logarithms that aren't exact are irrational, so a float will always tell them apart
+            (Self::Log(_), _) | (_, Self::Log(_)) => f64::try_from(self.clone())
+                .ok()?
+                .partial_cmp(&f64::try_from(other.clone()).ok()?),
+            (Self::RadicalSum(_), _) | (_, Self::RadicalSum(_)) => match (self.clone() - other.clone()).ok()? {
+                Self::Int(0) => Some(Ordering::Equal),
+                difference => f64::try_from(difference).ok()?.partial_cmp(&0.),
+            },
             (Self::Int(a), Self::Int(b)) => Some(a.cmp(b)),
             (Self::Float(a), Self::Int(b)) => a.partial_cmp(&(*b as f64)),
             (Self::Rational(a), Self::Int(b)) => a.partial_cmp(&Ratio::from(*b)),
             (Self::Symbol(a), Self::Int(b)) => a.symbol_eval().ok()?.partial_cmp(&(*b as f64)),
             (Self::Radical(a), Self::Int(b)) => {
                 let (index, radicand) = (a.index, *a.radicand.clone());
-                println!("{} root {:?} ? {}", index, radicand, b);
-                let (lneg, rneg) = (a.coefficient < 0.into(), *b < 0);
-                let rhs = Ratio::from(*b) / a.coefficient;
+                let (lneg, rneg) = (a.coefficient.is_negative(), *b < 0);
+                let rhs = BigRational::from(BigInt::from(*b)) / &a.coefficient;
                 let result = if lneg { -radicand } else { radicand }.partial_cmp(&if rneg {
-                    -Self::Rational(rhs.pow(index as i32).abs())
+                    -Self::from(rhs.pow(index as i32).abs())
                 } else {
-                    Self::Rational(rhs.pow(index as i32).abs())
+                    Self::from(rhs.pow(index as i32).abs())
                 });
-                println!("{:?}", result);
                 result
             }
             (Self::Symbolic(a), Self::Int(b)) => {
@@ -123,33 +148,25 @@ impl std::cmp::PartialOrd for Data {
                         }
                     }
                 } else {
-                    a.as_float().partial_cmp(&b.as_float())
+                    a.as_float().ok().partial_cmp(&b.as_float().ok())
                 }
             }
             (&Self::Float(a), &Self::Radical(b)) => a.partial_cmp(&b.clone().as_float().ok()?),
             (&Self::Radical(a), &Self::Radical(b)) => {
                 let a = a.clone();
                 let b = b.clone();
-                println!("{:?} is being compared with {:?}", a, b);
                 if a.index == b.index {
                     // easily done, this will be nearly every case because this is mostly sqrts
                     let i = a.index;
-                    let (lneg, rneg) = (a.coefficient < 0.into(), b.coefficient < 0.into());
+                    let (lneg, rneg) = (a.coefficient.is_negative(), b.coefficient.is_negative());
                     let mut should_flip = *a.radicand < Self::Int(0);
-                    should_flip ^= b.coefficient < 0.into(); // the rarely used XOR-Assignment operator, both is true, it should be false
+                    should_flip ^= b.coefficient.is_negative(); // the rarely used XOR-Assignment operator, both is true, it should be false
                     let m = a.coefficient;
                     let n = b.coefficient;
                     let mpow = m.pow(i as i32);
                     let npow = n.pow(i as i32);
-                    println!("SameIndex_Coeffs: {:?} / {:?} = ...", mpow, npow);
-                    let lhs = Self::Rational(mpow / npow);
-                    println!("{:?}", lhs);
-                    println!(
-                        "SameIndex_Radicands: {:?} / {:?} = ...",
-                        b.radicand, a.radicand
-                    );
+                    let lhs = Self::from(mpow / npow);
                     let r_rhs = (*b.radicand / (*a.radicand)).ok()?;
-                    println!("{:?}", r_rhs);
                     let result = lhs.partial_cmp(&r_rhs).map(|o| {
                         if should_flip {
                             o.reverse()
@@ -163,21 +180,20 @@ impl std::cmp::PartialOrd for Data {
                             o
                         }
                     }); // if should flip, flip it
-                    println!("{:?}", result);
                     result
                 } else {
-                    let (lneg, rneg) = (a.coefficient < 0.into(), b.coefficient < 0.into());
+                    let (lneg, rneg) = (a.coefficient.is_negative(), b.coefficient.is_negative());
                     let k = lcm(a.index, b.index) as i32; // lowest common multiple of the indices
                     let m = a.coefficient.abs();
                     let n = b.coefficient.abs();
                     let lhs = { |x: Data| if lneg { -x } else { x } }(
                         // flip if negative
-                        (Self::Rational(m.pow(k))
+                        (Self::from(m.pow(k))
                             * a.radicand.pow(Data::from(k as i64 / a.index as i64)).ok()?)
                         .ok()?,
                     );
                     let rhs = { |x: Data| if rneg { -x } else { x } }(
-                        (Self::Rational(n.pow(k))
+                        (Self::from(n.pow(k))
                             * b.radicand.pow(Data::from(k as i64 / b.index as i64)).ok()?)
                         .ok()?,
                     );
@@ -186,12 +202,12 @@ impl std::cmp::PartialOrd for Data {
             }
             (Self::Radical(a), Self::Rational(b)) => {
                 let (index, radicand) = (a.index, *a.radicand.clone());
-                let (lneg, rneg) = (a.coefficient < 0.into(), b < &0.into());
-                let rhs = b / a.coefficient.abs();
+                let (lneg, rneg) = (a.coefficient.is_negative(), b < &0.into());
+                let rhs = big(*b) / a.coefficient.abs();
                 if lneg { -radicand } else { radicand }.partial_cmp(&if rneg {
-                    -Self::Rational(rhs.pow(index as i32).abs())
+                    -Self::from(rhs.pow(index as i32).abs())
                 } else {
-                    Self::Rational(rhs.pow(index as i32).abs())
+                    Self::from(rhs.pow(index as i32).abs())
                 })
             }
             (Self::Rational(a), Self::Rational(b)) => a.partial_cmp(&b),
@@ -224,21 +240,10 @@ impl std::cmp::PartialOrd for Data {
     }
 }
 
-trait Abs {
-    fn abs(self) -> Self;
-}
-
-impl Abs for Ratio<i64> {
-    fn abs(self) -> Self {
-        let (n, d) = self.into();
-        (n.abs(), d.abs()).into()
-    }
-}
-
 #[cfg(test)]
 mod test {
     use crate::eval::{radical::Radical, Data, Symbolic};
-    use num::rational::Ratio;
+    use num::bigint::BigInt;
     use rand::Rng;
     #[test]
     fn ints() {
@@ -258,26 +263,26 @@ mod test {
     #[test]
     fn roots() {
         assert!(
-            Data::Radical(Radical::new(3.into(), 2, Data::from(2).into())).partial_cmp(
-                &Data::Radical(Radical::new(4.into(), 2, Data::from(2).into()))
+            Data::Radical(Radical::new(BigInt::from(3).into(), 2, Data::from(2).into())).partial_cmp(
+                &Data::Radical(Radical::new(BigInt::from(4).into(), 2, Data::from(2).into()))
             ) == Some(std::cmp::Ordering::Less)
         ); // distinguish by coefficient
-        assert!(Data::Int(2) > Data::Radical(Radical::new(1.into(), 2, Data::from(2).into()))); // distinguish from int
+        assert!(Data::Int(2) > Data::Radical(Radical::new(BigInt::from(1).into(), 2, Data::from(2).into()))); // distinguish from int
         assert!(
-            Data::Radical(Radical::new(1.into(), 2, Data::from(19).into()))
-                > Data::Radical(Radical::new(3.into(), 2, Data::from(2).into()))
+            Data::Radical(Radical::new(BigInt::from(1).into(), 2, Data::from(19).into()))
+                > Data::Radical(Radical::new(BigInt::from(3).into(), 2, Data::from(2).into()))
         ); // distinguish complex
     }
 
     #[test]
     fn roots_negatives_cursory() {
         assert!(
-            Data::Radical(Radical::new_raw(Ratio::from(-1), 2, Data::from(2).into()))
-                < Data::Radical(Radical::new_raw(Ratio::from(1), 2, Data::from(2).into()))
+            Data::Radical(Radical::new_raw(BigInt::from(-1).into(), 2, Data::from(2).into()))
+                < Data::Radical(Radical::new_raw(BigInt::from(1).into(), 2, Data::from(2).into()))
         );
         assert!(
-            Data::Radical(Radical::new_raw(Ratio::from(-90), 3, Data::from(-2).into()))
-                > Data::Radical(Radical::new_raw(Ratio::from(-1), 3, Data::from(2).into()))
+            Data::Radical(Radical::new_raw(BigInt::from(-90).into(), 3, Data::from(-2).into()))
+                > Data::Radical(Radical::new_raw(BigInt::from(-1).into(), 3, Data::from(2).into()))
         );
     }
 
@@ -305,9 +310,9 @@ mod test {
             assert_eq!(
                 {
                     let lhs =
-                        Data::Radical(Radical::new((2 * m).into(), 3, Data::from(2 * a).into()));
+                        Data::Radical(Radical::new(BigInt::from(2 * m).into(), 3, Data::from(2 * a).into()));
                     println!("lhs: {:?}", lhs);
-                    let rhs = Data::Radical(Radical::new(n.into(), 3, Data::from(2 * b).into()));
+                    let rhs = Data::Radical(Radical::new(BigInt::from(n).into(), 3, Data::from(2 * b).into()));
                     println!("rhs: {:?}", rhs);
                     lhs.partial_cmp(&rhs)
                 },
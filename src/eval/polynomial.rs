@@ -0,0 +1,236 @@
+/*! Polynomials in free variables, like `2x + 1` or `x^2 y - 3/4`, with exact `Data` coefficients.
+Like terms are always collected, and products are always expanded */
+
+use crate::error::Error;
+use super::Data;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+/// A product of variables raised to powers, like `x^2 y`, sorted by the names of the variables.
+/// The empty monomial is the constant term
+pub type Monomial = BTreeMap<String, u32>;
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct Polynomial {
+    terms: BTreeMap<Monomial, Data>,
+}
+
+impl Polynomial {
+    /// a single free variable, `x`
+    pub fn variable(name: String) -> Self {
+        let mut monomial = Monomial::new();
+        monomial.insert(name, 1);
+        Self::term(monomial, Data::Int(1))
+    }
+
+    pub fn constant(value: Data) -> Self {
+        Self::term(Monomial::new(), value)
+    }
+
+    fn term(monomial: Monomial, coeff: Data) -> Self {
+        let mut terms = BTreeMap::new();
+        if coeff != Data::Int(0) {
+            terms.insert(monomial, coeff);
+        }
+        Polynomial { terms }
+    }
+
+    /// the terms, highest degree first, so they come out in the order you'd write them
+    pub fn terms(&self) -> Vec<(&Monomial, &Data)> {
+        let mut terms: Vec<_> = self.terms.iter().collect();
+        terms.sort_by(|(a, _), (b, _)| degree(b).cmp(&degree(a)).then(a.cmp(b)));
+        terms
+    }
+
+    /// all the free variables that show up anywhere in the polynomial
+    pub fn variables(&self) -> Vec<&String> {
+        let mut variables: Vec<_> = self.terms.keys().flat_map(|m| m.keys()).collect();
+        variables.sort();
+        variables.dedup();
+        variables
+    }
+
+    /// the coefficient of `variable^power`, treating every other variable as part of the coefficient
+    /// returns `None` if another variable shows up
+    pub fn coefficient_of(&self, variable: &str, power: u32) -> Option<Data> {
+        let mut result = Data::Int(0);
+        for (monomial, coeff) in &self.terms {
+            if monomial.keys().any(|v| v != variable) {
+                return None;
+            }
+            if monomial.get(variable).copied().unwrap_or(0) == power {
+                result = (result + coeff.clone()).ok()?;
+            }
+        }
+        Some(result)
+    }
+
+    /// the highest power of `variable` that shows up
+    pub fn degree_in(&self, variable: &str) -> u32 {
+        self.terms
+            .keys()
+            .map(|m| m.get(variable).copied().unwrap_or(0))
+            .max()
+            .unwrap_or(0)
+    }
+
+    fn insert_term(&mut self, monomial: Monomial, coeff: Data) -> Result<(), Error> {
+        let sum = match self.terms.remove(&monomial) {
+            Some(existing) => (existing + coeff)?,
+            None => coeff,
+        };
+        if sum != Data::Int(0) {
+            self.terms.insert(monomial, sum);
+        }
+        Ok(())
+    }
+
+    pub fn add(mut self, rhs: Self) -> Result<Self, Error> {
+        for (monomial, coeff) in rhs.terms {
+            self.insert_term(monomial, coeff)?;
+        }
+        Ok(self)
+    }
+
+    /// multiplies out every pair of terms
+    pub fn mul(self, rhs: &Self) -> Result<Self, Error> {
+        let mut product = Polynomial {
+            terms: BTreeMap::new(),
+        };
+        for (l_monomial, l_coeff) in &self.terms {
+            for (r_monomial, r_coeff) in &rhs.terms {
+                let mut monomial = l_monomial.clone();
+                for (variable, power) in r_monomial {
+                    *monomial.entry(variable.clone()).or_insert(0) += power;
+                }
+                product.insert_term(monomial, (l_coeff.clone() * r_coeff.clone())?)?;
+            }
+        }
+        Ok(product)
+    }
+
+    /// Division only works out when the divisor is a single term that goes into every term,
+    /// anything else would need rational functions, which we don't have
+    pub fn div(self, rhs: Self) -> Result<Self, Error> {
+        let (r_monomial, r_coeff) = match rhs.terms.iter().collect::<Vec<_>>().as_slice() {
+            [(m, c)] => ((*m).clone(), (*c).clone()),
+            [] => return Err(Error::domain("Divide by 0")),
+            _ => {
+                return Err(Error::algebra(format!(
+                    "can't divide by {}, it has more than one term",
+                    Data::from(rhs.clone())
+                )))
+            }
+        };
+        let mut quotient = Polynomial {
+            terms: BTreeMap::new(),
+        };
+        for (mut monomial, coeff) in self.terms {
+            for (variable, power) in &r_monomial {
+                match monomial.get_mut(variable) {
+                    Some(p) if *p >= *power => *p -= power,
+                    _ => {
+                        return Err(Error::algebra(format!(
+                            "can't divide by {}, it doesn't go into every term",
+                            Data::from(Polynomial::term(r_monomial.clone(), r_coeff.clone()))
+                        )))
+                    }
+                }
+            }
+            monomial.retain(|_, p| *p != 0);
+            quotient.insert_term(monomial, (coeff / r_coeff.clone())?)?;
+        }
+        Ok(quotient)
+    }
+
+    pub fn pow(self, power: u32) -> Result<Self, Error> {
+        let mut result = Polynomial::constant(Data::Int(1));
+        for _ in 0..power {
+            result = result.mul(&self)?;
+        }
+        Ok(result)
+    }
+
+    /// the partial derivative with respect to `variable`, term by term
+    pub fn derivative(&self, variable: &str) -> Result<Self, Error> {
+        let mut result = Polynomial {
+            terms: BTreeMap::new(),
+        };
+        for (monomial, coeff) in &self.terms {
+            if let Some(&power) = monomial.get(variable) {
+                let mut monomial = monomial.clone();
+                match power {
+                    1 => monomial.remove(variable),
+                    _ => monomial.insert(variable.to_string(), power - 1),
+                };
+                result.insert_term(monomial, (coeff.clone() * Data::Int(power as i64))?)?;
+            }
+        }
+        Ok(result)
+    }
+
+    pub fn neg(self) -> Self {
+        Polynomial {
+            terms: self.terms.into_iter().map(|(m, c)| (m, -c)).collect(),
+        }
+    }
+}
+
+/// the total degree of a monomial, `x^2 y` has degree 3
+fn degree(monomial: &Monomial) -> u32 {
+    monomial.values().sum()
+}
+
+impl From<Data> for Polynomial {
+    fn from(data: Data) -> Self {
+        match data {
+            Data::Polynomial(p) => p,
+            other => Polynomial::constant(other),
+        }
+    }
+}
+
+impl From<Polynomial> for Data {
+    /// polynomials with no variables left in them are just numbers
+    fn from(p: Polynomial) -> Self {
+        match p.terms.len() {
+            0 => Data::Int(0),
+            1 if p.terms.contains_key(&Monomial::new()) => {
+                p.terms.into_iter().next().map(|(_, c)| c).unwrap() // we just checked there's one
+            }
+            _ => Data::Polynomial(p),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::eval::op::pow::Pow;
+
+    fn x() -> Data {
+        Data::Polynomial(Polynomial::variable("x".into()))
+    }
+
+    #[test]
+    fn collects_like_terms() {
+        let lhs = (x() + Data::Int(1)).unwrap().pow(Data::Int(2)).unwrap();
+        let rhs = x().pow(Data::Int(2)).unwrap();
+        assert_eq!(format!("{}", (lhs - rhs).unwrap()), "2x + 1");
+    }
+
+    #[test]
+    fn cancels_down_to_numbers() {
+        let y = Data::Polynomial(Polynomial::variable("y".into()));
+        let expr = ((x() * y.clone()).unwrap() - (y * x()).unwrap()).unwrap();
+        assert_eq!(expr, Data::Int(0));
+        assert_eq!(((x() * Data::Int(6)).unwrap() / x()).unwrap(), Data::Int(6));
+    }
+
+    #[test]
+    fn keeps_coefficients_exact() {
+        let expr = ((x() / Data::Int(3)).unwrap() + (x() / Data::Int(6)).unwrap()).unwrap();
+        assert_eq!(format!("{}", expr), "(1/2)x");
+        assert!((Data::Int(1) / x()).is_err());
+    }
+}
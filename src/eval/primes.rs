@@ -0,0 +1,351 @@
+/*! Primes, testing for them and breaking whole numbers down into them */
+
+use super::Data;
+use crate::error::Error;
+use num::bigint::BigInt;
+use num::integer::Integer;
+use num::traits::{One, Signed, ToPrimitive, Zero};
+use serde::{Deserialize, Serialize};
+
+/// A whole number written as a product of primes, like `2^3 × 3`, which is what `factor` gives back.
+/// Doing anything else with it uses the number it's a factorisation of
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct Factorisation {
+    pub negative: bool,
+    /// the primes, smallest first, and how many times each one goes in
+    pub factors: Vec<(BigInt, u32)>,
+}
+
+impl Factorisation {
+    /// `n` broken all the way down into primes, which is an error if part of it is too hard to split
+    pub fn of(n: &BigInt) -> Result<Self, Error> {
+        let factors = factorise_big(n);
+        // `factorise_big` keeps anything Pollard's rho can't split in one piece
+        if let Some((p, _)) = factors.iter().find(|(p, _)| p.to_u64().is_none() && !is_prime_big(p)) {
+            return Err(Error::Overflow(format!(
+                "{} isn't prime, but its factors are too big to find",
+                p
+            )));
+        }
+        Ok(Factorisation {
+            negative: n.is_negative(),
+            factors,
+        })
+    }
+
+    /// the number this is a factorisation of
+    pub fn value(&self) -> BigInt {
+        let magnitude = self
+            .factors
+            .iter()
+            .map(|(p, k)| p.pow(*k))
+            .product::<BigInt>();
+        if self.negative {
+            -magnitude
+        } else {
+            magnitude
+        }
+    }
+}
+
+impl From<Factorisation> for Data {
+    fn from(f: Factorisation) -> Self {
+        Data::Factorisation(f)
+    }
+}
+
+/// The Miller-Rabin bases that are known to get every `u64` right
+const BASES: [u64; 12] = [2, 3, 5, 7, 11, 13, 17, 19, 23, 29, 31, 37];
+
+/// Is `n` prime, this is Miller-Rabin with the bases that are known to get every `u64` right
+pub fn is_prime(n: u64) -> bool {
+    if n < 2 {
+        return false;
+    }
+    for p in BASES {
+        if n.is_multiple_of(p) {
+            return n == p;
+        }
+    }
+    // n - 1 = d 2^s with d odd
+    let s = (n - 1).trailing_zeros();
+    let d = (n - 1) >> s;
+    'bases: for a in BASES {
+        let mut x = pow_mod(a, d, n);
+        if x == 1 || x == n - 1 {
+            continue;
+        }
+        for _ in 1..s {
+            x = mul_mod(x, x, n);
+            if x == n - 1 {
+                continue 'bases;
+            }
+        }
+        return false;
+    }
+    true
+}
+
+/// `is_prime` for numbers of any size. Past a `u64` the bases aren't known to get everything right,
+/// but a composite number getting past all of them is vanishingly unlikely
+pub fn is_prime_big(n: &BigInt) -> bool {
+    if n.is_negative() {
+        return false;
+    }
+    if let Some(n) = n.to_u64() {
+        return is_prime(n);
+    }
+    // n - 1 = d 2^s with d odd
+    let n_minus_one: BigInt = n - 1;
+    let s = n_minus_one.trailing_zeros().unwrap_or(0);
+    let d = &n_minus_one >> s;
+    'bases: for a in BASES {
+        if (n % a).is_zero() {
+            return false;
+        }
+        let mut x = BigInt::from(a).modpow(&d, n);
+        if x.is_one() || x == n_minus_one {
+            continue;
+        }
+        for _ in 1..s {
+            x = &x * &x % n;
+            if x == n_minus_one {
+                continue 'bases;
+            }
+        }
+        return false;
+    }
+    true
+}
+
+/// Small factors are quicker to find by just trying them, Pollard's rho is for whatever's left
+const TRIAL_DIVISION_LIMIT: u64 = 1_000;
+
+/// How long Pollard's rho gets on a number past a `u64`, for each `c`, before giving up on splitting it,
+/// which finds most factors up to about the square of this
+const BIG_RHO_STEPS: u32 = 1 << 16;
+
+/// How many different `c`s Pollard's rho tries on a number past a `u64`
+const BIG_RHO_SEEDS: u32 = 2;
+
+/// How many differences Pollard's rho multiplies together before taking one gcd of all of them
+const BIG_RHO_BATCH: u32 = 128;
+
+/// The prime factors of `n`, smallest first, with how many times each goes in. 0 and 1 have none
+pub fn factorise(n: u64) -> Vec<(u64, u32)> {
+    if n == 0 {
+        return Vec::new();
+    }
+    let mut n = n;
+    let mut primes = Vec::new();
+    let mut p = 2;
+    while p < TRIAL_DIVISION_LIMIT && p <= n / p {
+        while n.is_multiple_of(p) {
+            primes.push(p);
+            n /= p;
+        }
+        p += 1;
+    }
+    split(n, &mut primes);
+    count_repeats(primes)
+}
+
+/// `factorise` for numbers of any size, ignoring the sign. Past a `u64` anything that Pollard's rho
+/// can't split in `BIG_RHO_STEPS` steps is kept in one piece, even though it might not be prime
+pub fn factorise_big(n: &BigInt) -> Vec<(BigInt, u32)> {
+    let in_big = |factors: Vec<(u64, u32)>| factors.into_iter().map(|(p, k)| (BigInt::from(p), k));
+    let mut n = n.abs();
+    if let Some(n) = n.to_u64() {
+        return in_big(factorise(n)).collect();
+    }
+    let mut primes = Vec::new();
+    for p in 2..TRIAL_DIVISION_LIMIT {
+        while (&n % p).is_zero() {
+            n /= p;
+            primes.push(BigInt::from(p));
+        }
+    }
+    split_big(n, &mut primes);
+    count_repeats(primes)
+}
+
+/// the primes, smallest first, with how many times each one turns up
+fn count_repeats<T: Ord>(mut primes: Vec<T>) -> Vec<(T, u32)> {
+    primes.sort_unstable();
+    let mut factors: Vec<(T, u32)> = Vec::new();
+    for p in primes {
+        match factors.last_mut() {
+            Some((q, k)) if *q == p => *k += 1,
+            _ => factors.push((p, 1)),
+        }
+    }
+    factors
+}
+
+/// `split` for numbers of any size
+fn split_big(n: BigInt, primes: &mut Vec<BigInt>) {
+    if let Some(n) = n.to_u64() {
+        for (p, k) in factorise(n) {
+            for _ in 0..k {
+                primes.push(BigInt::from(p));
+            }
+        }
+    } else if is_prime_big(&n) {
+        primes.push(n);
+    } else {
+        match pollard_rho_big(&n) {
+            Some(d) => {
+                let rest = &n / &d;
+                split_big(d, primes);
+                split_big(rest, primes);
+            }
+            None => primes.push(n),
+        }
+    }
+}
+
+/// `pollard_rho` for numbers past a `u64`, with Brent's cycle finding, which only steps once at a time
+/// and takes one gcd every `BIG_RHO_BATCH` steps. This gives up after `BIG_RHO_STEPS` steps on each `c`
+fn pollard_rho_big(n: &BigInt) -> Option<BigInt> {
+    'seeds: for c in 1..=BIG_RHO_SEEDS {
+        let step = |x: &BigInt| (x * x + c) % n;
+        let (mut y, mut product) = (BigInt::from(2), BigInt::one());
+        // y runs ahead of x, which jumps to y every time the distance between them doubles
+        let mut distance = 1;
+        while distance <= BIG_RHO_STEPS {
+            let x = y.clone();
+            for _ in 0..distance {
+                y = step(&y);
+            }
+            let mut taken = 0;
+            while taken < distance {
+                let batch_start = y.clone();
+                let batch = BIG_RHO_BATCH.min(distance - taken);
+                for _ in 0..batch {
+                    y = step(&y);
+                    product = product * (&x - &y).abs() % n;
+                }
+                taken += batch;
+                let d = product.gcd(n);
+                if d.is_one() {
+                    continue;
+                }
+                if d != *n {
+                    return Some(d);
+                }
+                // the whole batch went past the factor, so go back through it one step at a time
+                let mut y = batch_start;
+                for _ in 0..batch {
+                    y = step(&y);
+                    let d = (&x - &y).abs().gcd(n);
+                    if d == *n {
+                        break;
+                    } else if !d.is_one() {
+                        return Some(d);
+                    }
+                }
+                continue 'seeds;
+            }
+            distance *= 2;
+        }
+    }
+    None
+}
+
+/// Breaks `n`, which has no factors below `TRIAL_DIVISION_LIMIT`, all the way down into primes
+fn split(n: u64, primes: &mut Vec<u64>) {
+    if n == 1 {
+        return;
+    }
+    if is_prime(n) {
+        primes.push(n);
+    } else {
+        let d = pollard_rho(n);
+        split(d, primes);
+        split(n / d, primes);
+    }
+}
+
+/// A factor of `n` other than 1 and itself, `n` has to be composite.
+/// This walks `x -> x^2 + c mod n` at two speeds until they land on the same thing mod some factor,
+/// trying another `c` if they land on the same thing mod `n` itself
+fn pollard_rho(n: u64) -> u64 {
+    for c in 1..n {
+        let step = |x: u64| ((x as u128 * x as u128 + c as u128) % n as u128) as u64;
+        let (mut slow, mut fast, mut d) = (2, 2, 1);
+        while d == 1 {
+            slow = step(slow);
+            fast = step(step(fast));
+            d = slow.abs_diff(fast).gcd(&n);
+        }
+        if d != n {
+            return d;
+        }
+    }
+    unreachable!("{} is composite, so some c finds a factor", n)
+}
+
+fn mul_mod(a: u64, b: u64, m: u64) -> u64 {
+    ((a as u128 * b as u128) % m as u128) as u64
+}
+
+fn pow_mod(base: u64, exponent: u64, m: u64) -> u64 {
+    let (mut result, mut base, mut exponent) = (1, base % m, exponent);
+    while exponent > 0 {
+        if exponent & 1 == 1 {
+            result = mul_mod(result, base, m);
+        }
+        base = mul_mod(base, base, m);
+        exponent >>= 1;
+    }
+    result
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn finds_primes() {
+        let small: Vec<u64> = (0..50).filter(|n| is_prime(*n)).collect();
+        assert_eq!(small, vec![2, 3, 5, 7, 11, 13, 17, 19, 23, 29, 31, 37, 41, 43, 47]);
+        assert!(is_prime(1_000_000_007));
+        assert!(is_prime(18_446_744_073_709_551_557)); // the biggest u64 prime
+        assert!(!is_prime(3_215_031_751)); // a strong pseudoprime to 2, 3, 5 and 7
+        assert!(is_prime_big(&(BigInt::from(2).pow(89u32) - 1)));
+        assert!(!is_prime_big(&(BigInt::from(2).pow(67u32) - 1)));
+    }
+
+    #[test]
+    fn factorises() {
+        assert_eq!(factorise(360), vec![(2, 3), (3, 2), (5, 1)]);
+        assert_eq!(factorise(1), vec![]);
+        assert_eq!(factorise(97), vec![(97, 1)]);
+        // too big for trial division to get anywhere
+        assert_eq!(factorise(1_000_000_007 * 998_244_353), vec![(998_244_353, 1), (1_000_000_007, 1)]);
+        assert_eq!(factorise(4_294_967_291 * 4_294_967_291), vec![(4_294_967_291, 2)]);
+        assert_eq!(
+            factorise_big(&(BigInt::from(3).pow(50) * 1_000_000_007)),
+            vec![(BigInt::from(3), 50), (BigInt::from(1_000_000_007), 1)]
+        );
+        // 2^64 + 1 = 274177 × 67280421310721, neither of which trial division gets to
+        assert_eq!(
+            factorise_big(&(BigInt::from(2).pow(64u32) + 1)),
+            vec![(BigInt::from(274_177), 1), (BigInt::from(67_280_421_310_721u64), 1)]
+        );
+        assert_eq!(Factorisation::of(&BigInt::from(-360)).unwrap().value(), BigInt::from(-360));
+    }
+
+    #[test]
+    fn factorises_semiprimes() {
+        // neither factor is small enough for trial division, and the smaller one needs more steps than a few thousand
+        let mersenne: BigInt = BigInt::from(2).pow(61u32) - 1;
+        assert_eq!(
+            factorise_big(&(&mersenne * 1_000_000_007)),
+            vec![(BigInt::from(1_000_000_007), 1), (mersenne.clone(), 1)]
+        );
+        // the smaller factor is far too big for Pollard's rho, so this can't be broken down, but it isn't prime
+        let too_hard = &mersenne * (BigInt::from(2).pow(89u32) - 1);
+        assert!(matches!(Factorisation::of(&too_hard), Err(Error::Overflow(_))));
+    }
+}
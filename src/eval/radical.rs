@@ -1,56 +1,57 @@
-use super::{op::pow::Pow, op::root::NthRoot, ratio_as_float, Data, DivisibleBy};
-use num::rational::Ratio;
+use crate::error::Error;
+use super::{big::big_rational_as_float, op::pow::Pow, op::root::NthRoot, Data, DivisibleBy};
+use super::primes::factorise_big;
+use num::bigint::BigInt;
+use num::integer::Integer;
+use num::rational::{BigRational, Ratio};
+use num::traits::{One, Signed, ToPrimitive, Zero};
+use serde::{Deserialize, Serialize};
 use std::convert::TryFrom;
 
-#[derive(Clone, Debug, PartialEq)]
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
 pub struct Radical {
-    pub coefficient: Ratio<i64>,
+    /// big, so that taking a big perfect power out of the radicand can't overflow it
+    pub coefficient: BigRational,
     pub index: u32,
     pub radicand: Box<Data>,
 }
 
-const PRIMES_TO_50: [u16; 15] = [2, 3, 5, 7, 11, 13, 17, 19, 23, 29, 31, 37, 41, 43, 47];
-
-fn primes_to_k<'a>(k: u16) -> Vec<u16> {
-    let primes = PRIMES_TO_50
-        .iter()
-        .copied()
-        .take_while(move |n| *n < k)
-        .collect::<Vec<_>>();
-    primes
-}
-
 impl Radical {
-    pub fn simplify(self) -> Result<Self, String> {
-        let possible_extractible_factors = primes_to_k(
-            f64::try_from(self.radicand.clone().nth_root(self.index as i64)?)?.ceil() as u16,
-        )
-        .iter()
-        .map(|x| (x.pow(self.index), *x as u16))
-        .filter(|x| {
-            let data = &*self.radicand;
-            data.divisible_by(x.1)
+    /// Takes every perfect power out of the radicand, so `√72` is `6√2`, `∛(-16)` is `-2∛2` and `⁴√4` is `√2`.
+    /// Fractions come out of the bottom, `√(1/2)` is `(1/2)√2`.
+    /// Only whole numbers and fractions get simplified
+    pub fn simplify(self) -> Result<Self, Error> {
+        let r = match self.radicand.to_big_rational() {
+            Some(r) if !r.is_zero() => r,
+            _ => return Ok(self),
+        };
+        // odd roots of negatives are negative, even ones we can't do anything with here
+        let (sign, r) = match (r.is_negative(), !self.index.is_multiple_of(2)) {
+            (false, _) => (1, r),
+            (true, true) => (-1, -r),
+            (true, false) => return Ok(self),
+        };
+        // ⁿ√(p/q) = ⁿ√(p q^(n - 1)) / q
+        let mut powers = prime_powers(r.numer(), self.index);
+        for (q, k) in prime_powers(r.denom(), self.index) {
+            powers.push((q, k * (self.index - 1)));
+        }
+        // ⁴√(2^2) = √2, so the index can come down by anything that goes into every power
+        let shrink = powers.iter().fold(self.index, |g, (_, k)| g.gcd(&(k % self.index)));
+        let (index, mut outside, mut inside) = (self.index / shrink, BigInt::one(), BigInt::one());
+        for (p, k) in powers {
+            outside *= p.pow(k / self.index);
+            inside *= p.pow(k % self.index / shrink);
+        }
+        let extracted = BigRational::new(outside * sign, r.denom().clone());
+        Ok(Radical {
+            coefficient: self.coefficient * extracted,
+            index,
+            radicand: Box::new(Data::from(inside)),
         })
-        .collect();
-        self.simplify_by(possible_extractible_factors)
     }
 
-    fn simplify_by(self, factors: Vec<(u16, u16)>) -> Result<Self, String> {
-        match factors.as_slice() {
-            [] => Ok(self),
-            [(factor, root), ref factors @ ..] => {
-                let coefficient = self.coefficient * *root as i64;
-                let radicand = (*self.radicand / Data::Int(*factor as i64))?;
-                Radical {
-                    coefficient,
-                    index: self.index,
-                    radicand: radicand.into(),
-                }
-                .simplify_by(factors.to_vec())
-            }
-        }
-    }
-    pub fn new(coeff: Ratio<i64>, index: u32, radicand: Box<Data>) -> Self {
+    pub fn new(coeff: BigRational, index: u32, radicand: Box<Data>) -> Self {
         Self {
             coefficient: coeff,
             index,
@@ -60,7 +61,7 @@ impl Radical {
         .unwrap() // if this dies it's my fault
     }
 
-    pub fn new_raw(coeff: Ratio<i64>, index: u32, radicand: Box<Data>) -> Self {
+    pub fn new_raw(coeff: BigRational, index: u32, radicand: Box<Data>) -> Self {
         Self {
             coefficient: coeff,
             index,
@@ -69,20 +70,59 @@ impl Radical {
     }
 }
 
+/// `n` as a product of prime powers, or as near as we can get if it's too big to factorise
+fn prime_powers(n: &BigInt, index: u32) -> Vec<(BigInt, u32)> {
+    factorise_big(n)
+        .into_iter()
+        .map(|(p, k)| match p.to_u64() {
+            Some(_) => (p, k),
+            // too big to break down, but it might still be a perfect power
+            None => {
+                let root = p.nth_root(index);
+                if root.pow(index) == p {
+                    (root, k * index)
+                } else {
+                    (p, k)
+                }
+            }
+        })
+        .collect()
+}
+
+/// A radical with nothing left under it is just its coefficient
+impl From<Radical> for Data {
+    fn from(r: Radical) -> Self {
+        match *r.radicand {
+            Data::Int(1) => Data::from(r.coefficient),
+            Data::Int(0) => Data::Int(0),
+            _ if r.coefficient.is_zero() => Data::Int(0),
+            _ => Data::Radical(r),
+        }
+    }
+}
+
+/// The square root of a fraction, with every square factor taken out, so `√(8/3)` is `(2/3)√6`
+pub fn square_root(r: Ratio<i64>) -> Result<Data, Error> {
+    if r < Ratio::from(0) {
+        return Err(Error::non_real("even root of a negative number"));
+    }
+    Ok(Radical::new(BigRational::one(), 2, Data::from_ratio(r).into()).into())
+}
+
 impl DivisibleBy<&Ratio<i64>> for Radical {
     fn divisible_by(&self, rhs: &Ratio<i64>) -> bool {
-        self.coefficient.divisible_by(*rhs)
+        !rhs.is_zero() && (&self.coefficient / super::big::big(*rhs)).is_integer()
     }
 }
 
 impl DivisibleBy<u16> for Radical {
     fn divisible_by(&self, rhs: u16) -> bool {
-        self.coefficient.numer().divisible_by(rhs as i64)
+        self.divisible_by(rhs as i64)
     }
 }
 impl DivisibleBy<i64> for Radical {
     fn divisible_by(&self, rhs: i64) -> bool {
-        self.coefficient.numer().divisible_by(rhs)
+        rhs != 0 && self.coefficient.numer().is_multiple_of(&BigInt::from(rhs))
     }
 }
 
@@ -90,7 +130,7 @@ impl DivisibleBy<&Self> for Radical {
     fn divisible_by(&self, rhs: &Self) -> bool {
         // if we assume our radicals to be reduced, as I will, radicals are divisible if their radicands, indices are the same
         if self.radicand == rhs.radicand && self.index == rhs.index {
-            self.coefficient.divisible_by(rhs.coefficient)
+            !rhs.coefficient.is_zero() && (&self.coefficient / &rhs.coefficient).is_integer()
         } else {
             false
         }
@@ -98,17 +138,58 @@ impl DivisibleBy<&Self> for Radical {
 }
 
 impl Radical {
-    pub fn as_float(self) -> Result<f64, String> {
-        Ok(ratio_as_float(self.coefficient)
+    pub fn as_float(self) -> Result<f64, Error> {
+        Ok(big_rational_as_float(&self.coefficient)
             * f64::try_from(*self.radicand)?
                 .nth_root(self.index as i64)
-                .ok_or("Even root of negative number")?)
+                .ok_or_else(|| Error::non_real("even root of a negative number"))?)
     }
-    pub fn conjugate(self) -> Result<Self, String> {
+    pub fn conjugate(self) -> Result<Self, Error> {
         Ok(Self::new(
-            1.into(),
+            BigRational::one(),
             self.index,
             self.radicand.pow(Data::from(self.index as i64 - 1))?.into(),
         ))
     }
+
+    /// `1 / (c ⁿ√a)` as `(1 / (c a)) ⁿ√(a^(n - 1))`, which has no root left on the bottom
+    pub fn reciprocal(self) -> Result<Data, Error> {
+        let bottom = (Data::from(self.coefficient.clone()) * *self.radicand.clone())?;
+        Data::from(self.conjugate()?) / bottom
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::eval::big::big;
+
+    fn root(index: u32, radicand: Data) -> Data {
+        Radical::new(BigRational::one(), index, radicand.into()).into()
+    }
+
+    #[test]
+    fn takes_out_every_perfect_power() {
+        let surd = |coefficient: (i64, i64), index, radicand| {
+            Data::Radical(Radical::new_raw(big(coefficient.into()), index, Data::Int(radicand).into()))
+        };
+        assert_eq!(root(2, Data::Int(53 * 53 * 2)), surd((53, 1), 2, 2));
+        assert_eq!(root(3, Data::Int(-16)), surd((-2, 1), 3, 2));
+        assert_eq!(root(4, Data::Int(4)), surd((1, 1), 2, 2));
+        assert_eq!(root(2, Data::Rational((8, 3).into())), surd((2, 3), 2, 6));
+        assert_eq!(root(2, Data::Rational((1, 4).into())), Data::Rational((1, 2).into()));
+        assert_eq!(root(2, Data::Int(1_000_000_007 * 1_000_000_007 * 5)), surd((1_000_000_007, 1), 2, 5));
+        // even roots of negatives can't be done here, they're left alone
+        assert_eq!(root(2, Data::Int(-4)), surd((1, 1), 2, -4));
+    }
+
+    #[test]
+    fn big_coefficients_come_out_whole() {
+        let ten_to_the_twenty = BigInt::from(10).pow(20u32);
+        let radicand = Data::from(&ten_to_the_twenty * &ten_to_the_twenty * 3);
+        assert_eq!(
+            root(2, radicand),
+            Data::Radical(Radical::new_raw(ten_to_the_twenty.into(), 2, Data::Int(3).into()))
+        );
+    }
 }
@@ -0,0 +1,239 @@
+/*! Sums of radicals, like `1 + √2 - 3∛5`, with like radicals collected together so that they stay exact */
+
+use crate::error::Error;
+use super::{op::root::NthRoot, radical::Radical, Data};
+use num::traits::Zero;
+use serde::{Deserialize, Serialize};
+use std::cmp::Ordering;
+use std::convert::TryFrom;
+
+/// Each step of rationalising a denominator gets rid of one square root, and nobody types in more than this many
+const RATIONALISING_STEPS: usize = 16;
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct RadicalSum {
+    /// the part without any radicals in it, always an `Int`, `Rational` or a big one of those
+    pub rational: Data,
+    /// no two of these are alike, none of them are 0, and they're kept sorted so equal sums look equal
+    pub radicals: Vec<Radical>,
+}
+
+impl RadicalSum {
+    /// Both sides as radical sums, if there's a radical involved and everything else is exact,
+    /// which is when adding, multiplying or dividing them can be done without floats
+    pub fn both(lhs: &Data, rhs: &Data) -> Option<(Self, Self)> {
+        let has_radical = |x: &Data| matches!(x, Data::Radical(_) | Data::RadicalSum(_));
+        if has_radical(lhs) || has_radical(rhs) {
+            Some((Self::from_data(lhs)?, Self::from_data(rhs)?))
+        } else {
+            None
+        }
+    }
+
+    fn from_data(x: &Data) -> Option<Self> {
+        Some(match x {
+            Data::RadicalSum(s) => *s.clone(),
+            Data::Radical(r) => RadicalSum {
+                rational: Data::Int(0),
+                radicals: vec![r.clone()],
+            },
+            exact if exact.to_big_rational().is_some() => RadicalSum {
+                rational: exact.clone().unfactorise(),
+                radicals: Vec::new(),
+            },
+            _ => return None,
+        })
+    }
+
+    /// like radicals, with the same index and radicand, get their coefficients added
+    pub fn add(self, rhs: Self) -> Result<Self, Error> {
+        let mut radicals = self.radicals;
+        for r in rhs.radicals {
+            match radicals
+                .iter()
+                .position(|s| s.index == r.index && s.radicand == r.radicand)
+            {
+                Some(i) => {
+                    let coefficient = &radicals[i].coefficient + r.coefficient;
+                    if coefficient.is_zero() {
+                        radicals.remove(i);
+                    } else {
+                        radicals[i].coefficient = coefficient;
+                    }
+                }
+                None => radicals.push(r),
+            }
+        }
+        radicals.sort_by(|a, b| {
+            a.index
+                .cmp(&b.index)
+                .then_with(|| a.radicand.partial_cmp(&b.radicand).unwrap_or(Ordering::Equal))
+        });
+        Ok(RadicalSum {
+            rational: (self.rational + rhs.rational)?,
+            radicals,
+        })
+    }
+
+    pub fn neg(self) -> Self {
+        RadicalSum {
+            rational: -self.rational,
+            radicals: self
+                .radicals
+                .into_iter()
+                .map(|r| Radical {
+                    coefficient: -r.coefficient,
+                    ..r
+                })
+                .collect(),
+        }
+    }
+
+    /// every term times every term, `(1 + √2)(1 - √2) = 1 - √2 + √2 - 2 = -1`
+    pub fn mul(self, rhs: Self) -> Result<Data, Error> {
+        let (lhs, rhs) = (self.terms(), rhs.terms());
+        let mut product = Data::Int(0);
+        for a in &lhs {
+            for b in &rhs {
+                product = (product + (a.clone() * b.clone())?)?;
+            }
+        }
+        Ok(product)
+    }
+
+    /// Rationalises the denominator, by multiplying the top and bottom by the conjugate of one square root at a time
+    /// until there aren't any left on the bottom, so `1/(1 + √2) = (1 - √2)/(1 - 2) = √2 - 1`
+    pub fn div(self, rhs: Self) -> Result<Data, Error> {
+        let (mut top, mut bottom) = (Data::from(self), Data::from(rhs));
+        for _ in 0..RATIONALISING_STEPS {
+            let conjugate = match &bottom {
+                Data::RadicalSum(s) => match s.radicals.iter().rposition(|r| r.index == 2) {
+                    Some(i) => {
+                        let mut conjugate = *s.clone();
+                        conjugate.radicals[i].coefficient = -conjugate.radicals[i].coefficient.clone();
+                        Data::from(conjugate)
+                    }
+                    None => break,
+                },
+                Data::Radical(r) => Data::Radical(r.clone().conjugate()?),
+                // the bottom is rational now, so dividing by it is multiplying by its reciprocal
+                _ => return top * (Data::Int(1) / bottom)?,
+            };
+            top = (top * conjugate.clone())?;
+            bottom = (bottom * conjugate)?;
+        }
+        Ok(Data::Float(f64::try_from(top)? / f64::try_from(bottom)?))
+    }
+
+    /// `√(a + b√r)` as `√x ± √y`, whenever there are rationals `x` and `y` that work, like `√(3 + 2√2) = 1 + √2`.
+    /// Squaring `√x + √y` gives `x + y + 2√(xy)`, so `x + y = a` and `4xy = b^2 r`,
+    /// and the two of them are `(a ± √(a^2 - b^2 r))/2`, which needs that root to be rational
+    pub fn denest(&self) -> Option<Data> {
+        let surd = match self.radicals.as_slice() {
+            [surd] if surd.index == 2 => surd,
+            _ => return None,
+        };
+        let (a, b) = (self.rational.clone(), Data::from(surd.coefficient.clone()));
+        let b_squared_r = ((b.clone() * b.clone()).ok()? * *surd.radicand.clone()).ok()?;
+        let d = ((a.clone() * a.clone()).ok()? - b_squared_r).ok()?.nth_root(2).ok()?;
+        d.to_big_rational()?;
+        let x = ((a.clone() + d.clone()).ok()? / Data::Int(2)).ok()?;
+        let y = ((a - d).ok()? / Data::Int(2)).ok()?;
+        if y < Data::Int(0) {
+            return None;
+        }
+        let (root_x, root_y) = (x.nth_root(2).ok()?, y.nth_root(2).ok()?);
+        if b < Data::Int(0) {
+            (root_x - root_y).ok()
+        } else {
+            (root_x + root_y).ok()
+        }
+    }
+
+    pub fn as_float(self) -> Result<f64, Error> {
+        self.terms()
+            .into_iter()
+            .map(f64::try_from)
+            .sum()
+    }
+
+    /// the rational part and then each radical
+    fn terms(self) -> Vec<Data> {
+        std::iter::once(self.rational)
+            .chain(self.radicals.into_iter().map(Data::Radical))
+            .collect()
+    }
+}
+
+impl From<RadicalSum> for Data {
+    /// a sum with only one term left is just that term
+    fn from(s: RadicalSum) -> Self {
+        match (s.rational == Data::Int(0), s.radicals.len()) {
+            (_, 0) => s.rational,
+            (true, 1) => Data::from(s.radicals.into_iter().next().unwrap()),
+            _ => Data::RadicalSum(Box::new(s)),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn sqrt(n: i64) -> Data {
+        Data::Int(n).nth_root(2).unwrap()
+    }
+
+    #[test]
+    fn like_radicals_collect() {
+        assert_eq!((sqrt(2) + sqrt(8)).unwrap(), (Data::Int(3) * sqrt(2)).unwrap());
+        let sum = (Data::Int(1) + (sqrt(2) + sqrt(3)).unwrap()).unwrap();
+        assert_eq!(format!("{}", sum), "1 + √2 + √3");
+        assert_eq!(format!("{}", (sum - sqrt(3)).unwrap()), "1 + √2");
+        assert_eq!(((sqrt(2) + sqrt(3)).unwrap() - (sqrt(3) + sqrt(2)).unwrap()).unwrap(), Data::Int(0));
+    }
+
+    #[test]
+    fn multiplies_out() {
+        let (a, b) = ((Data::Int(1) + sqrt(2)).unwrap(), (Data::Int(1) - sqrt(2)).unwrap());
+        assert_eq!((a.clone() * b).unwrap(), Data::Int(-1));
+        assert_eq!(format!("{}", (a.clone() * a).unwrap()), "3 + 2√2");
+    }
+
+    #[test]
+    fn rationalises_denominators() {
+        let one_plus_root_two = (Data::Int(1) + sqrt(2)).unwrap();
+        assert_eq!(format!("{}", (Data::Int(1) / one_plus_root_two).unwrap()), "-1 + √2");
+        let root_two_plus_root_three = (sqrt(2) + sqrt(3)).unwrap();
+        assert_eq!(format!("{}", (Data::Int(1) / root_two_plus_root_three).unwrap()), "-√2 + √3");
+        let three_roots = (Data::Int(1) + (sqrt(2) + sqrt(3)).unwrap()).unwrap();
+        let reciprocal = (Data::Int(1) / three_roots.clone()).unwrap();
+        assert_eq!((reciprocal * three_roots).unwrap(), Data::Int(1));
+    }
+
+    #[test]
+    fn rationalises_single_radicals() {
+        let half = (Data::Int(1) / Data::Int(2)).unwrap();
+        let two_root_three = (Data::Int(2) * sqrt(3)).unwrap();
+        assert_eq!(format!("{}", (Data::Int(1) / two_root_three).unwrap()), "(1/6)√3");
+        assert_eq!(format!("{}", (half.clone() / sqrt(3)).unwrap()), "(1/6)√3");
+        let minus_half_root_three = ((Data::Int(0) - half).unwrap() * sqrt(3)).unwrap();
+        assert_eq!(format!("{}", (Data::Int(1) / minus_half_root_three).unwrap()), "(-2/3)√3");
+        let big = Data::from(num::BigInt::from(1) << 70);
+        assert_eq!(format!("{}", (big / sqrt(2)).unwrap()), "590295810358705651712√2");
+        assert_eq!((sqrt(2) / sqrt(8)).unwrap(), (Data::Int(1) / Data::Int(2)).unwrap());
+        assert_eq!(format!("{}", (sqrt(2) / sqrt(3)).unwrap()), "(1/3)√6");
+        let cbrt_two = Data::Int(2).nth_root(3).unwrap();
+        assert_eq!(format!("{}", (cbrt_two / sqrt(2)).unwrap()), "(1/2)⁶√32");
+    }
+
+    #[test]
+    fn denests() {
+        let three_plus_two_root_two = (Data::Int(3) + (Data::Int(2) * sqrt(2)).unwrap()).unwrap();
+        assert_eq!(format!("{}", three_plus_two_root_two.nth_root(2).unwrap()), "1 + √2");
+        let four_minus_two_root_three = (Data::Int(4) - (Data::Int(2) * sqrt(3)).unwrap()).unwrap();
+        assert_eq!(format!("{}", four_minus_two_root_three.nth_root(2).unwrap()), "-1 + √3");
+        let cant_denest = (Data::Int(1) + sqrt(2)).unwrap();
+        assert_eq!(format!("{}", cant_denest.nth_root(2).unwrap()), "√(1 + √2)");
+    }
+}
@@ -0,0 +1,435 @@
+/*! Solving polynomial equations in one variable, exactly wherever the roots can be written down,
+and with floats where they can't */
+
+use crate::error::Error;
+use super::{
+    big::big,
+    complex::Complex,
+    radical::{square_root, Radical},
+    env::Scope,
+    op::{calculate_fn::is_function, root::NthRoot},
+    Data, Environment,
+};
+use crate::parser::ExprTree;
+use num::integer::lcm;
+use num::rational::Ratio;
+use std::convert::TryFrom;
+
+/// One solution of an equation, `rational + surd`,
+/// because a rational and a radical can't be added together into one `Data` without becoming a float
+#[derive(Clone, Debug, PartialEq)]
+pub struct Solution {
+    pub rational: Data,
+    pub surd: Option<Radical>,
+}
+
+impl From<Data> for Solution {
+    fn from(rational: Data) -> Self {
+        Solution {
+            rational,
+            surd: None,
+        }
+    }
+}
+
+impl Solution {
+    /// roughly where the solution is, for putting them in order
+    fn approximate(&self) -> f64 {
+        let surd = match &self.surd {
+            Some(surd) => surd.clone().as_float().unwrap_or(f64::NAN),
+            None => 0.,
+        };
+        f64::try_from(self.rational.clone()).unwrap_or(f64::NAN) + surd
+    }
+}
+
+/// Solves `lhs = rhs` for `var`, or for the only variable in it if `var` is `None`.
+/// Gives back the variable that was solved for, and the solutions, smallest first.
+/// Quadratics, and quadratics in x^2, get their complex solutions too, anything higher only gets the real ones
+pub fn solve(
+    lhs: ExprTree,
+    rhs: ExprTree,
+    var: Option<String>,
+    env: &Environment,
+) -> Result<(String, Vec<Solution>), Error> {
+    let var = var.or_else(|| only_name_given_a_value(&lhs, &rhs, env));
+    let scope = match &var {
+        Some(var) => Scope::solving(env, var),
+        None => Scope::global(env),
+    };
+    let polynomial = match (lhs.eval_in(&scope)? - rhs.eval_in(&scope)?)? {
+        Data::Polynomial(p) => p,
+        Data::Int(0) => return Err(Error::algebra("there's nothing to solve, that's always true")),
+        _ => return Err(Error::algebra("there's nothing to solve, that's never true")),
+    };
+    let var = match (var, polynomial.variables().as_slice()) {
+        (Some(var), _) => var,
+        (None, [only]) => only.to_string(),
+        (None, variables) => {
+            return Err(Error::algebra(format!(
+                "which variable should be solved for? Try putting `, {}` on the end",
+                variables[0]
+            )))
+        }
+    };
+    let degree = polynomial.degree_in(&var);
+    if degree == 0 {
+        return Err(Error::algebra(format!("{} isn't in that equation", var)));
+    }
+    let coeffs = (0..=degree)
+        .map(|power| polynomial.coefficient_of(&var, power))
+        .collect::<Option<Vec<_>>>()
+        .ok_or_else(|| {
+            Error::algebra(format!(
+                "{} can only be solved for when it's the only variable",
+                var
+            ))
+        })?;
+    let mut solutions = solve_coefficients(coeffs)?;
+    solutions.sort_by(|a, b| {
+        a.approximate()
+            .partial_cmp(&b.approximate())
+            .unwrap_or(std::cmp::Ordering::Equal)
+    });
+    solutions.dedup();
+    Ok((var, solutions))
+}
+
+/// The one name in the equation, if that's all there is and it's been given a value,
+/// so that `let x = 3` doesn't stop `solve x^2 = 4` from solving for x
+fn only_name_given_a_value(lhs: &ExprTree, rhs: &ExprTree, env: &Environment) -> Option<String> {
+    let mut names = Vec::new();
+    names_in(lhs, env, &mut names);
+    names_in(rhs, env, &mut names);
+    match names.as_slice() {
+        [only] if env.get(only).is_some() => Some(only.clone()),
+        _ => None,
+    }
+}
+
+/// every name in `tree` that's a variable rather than a function
+fn names_in(tree: &ExprTree, env: &Environment, names: &mut Vec<String>) {
+    let mut add = |name: &String| {
+        if !names.contains(name) {
+            names.push(name.clone())
+        }
+    };
+    match tree {
+        ExprTree::Val(_) => {}
+        ExprTree::Ident(name) => add(name),
+        ExprTree::Call(name, args) => {
+            // `x(x + 1)` is x times x + 1 when there's no function called x
+            if args.len() == 1 && env.function(name).is_none() && !is_function(name) {
+                add(name);
+            }
+            args.iter().for_each(|arg| names_in(arg, env, names));
+        }
+        ExprTree::UNode(_, t) => names_in(t, env, names),
+        ExprTree::BNode(_, l, r) => {
+            names_in(l, env, names);
+            names_in(r, env, names);
+        }
+    }
+}
+
+/// the roots of `coeffs[0] + coeffs[1] x + coeffs[2] x^2 + ...`
+fn solve_coefficients(mut coeffs: Vec<Data>) -> Result<Vec<Solution>, Error> {
+    let mut solutions = Vec::new();
+    if coeffs[0] == Data::Int(0) {
+        solutions.push(Data::Int(0).into());
+        while coeffs[0] == Data::Int(0) {
+            coeffs.remove(0);
+        }
+    }
+    // past quadratics, the only roots that can be found exactly are rational ones
+    if coeffs.len() > 3 {
+        for candidate in rational_candidates(&coeffs) {
+            let mut is_root = false;
+            while coeffs.len() > 1 && horner(&coeffs, &candidate)? == Data::Int(0) {
+                coeffs = deflate(&coeffs, &candidate)?;
+                is_root = true;
+            }
+            if is_root {
+                solutions.push(candidate.into());
+            }
+        }
+    }
+    match coeffs.len() {
+        0 | 1 => {}
+        2 => solutions.push(((-coeffs[0].clone()) / coeffs[1].clone())?.into()),
+        3 => solutions.extend(quadratic(&coeffs[2], &coeffs[1], &coeffs[0])?),
+        // quadratics in x^2, like x^4 - 5x^2 + 6
+        5 if coeffs[1] == Data::Int(0) && coeffs[3] == Data::Int(0) => {
+            for u in quadratic(&coeffs[4], &coeffs[2], &coeffs[0])? {
+                match (u.surd, exact_ratio(&u.rational)) {
+                    (None, Some(r)) if r >= Ratio::from(0) => {
+                        let root = square_root(r)?;
+                        solutions.push((-root.clone()).into());
+                        solutions.push(root.into());
+                    }
+                    // negative and complex values of x^2 give complex x, which stay exact
+                    (None, _) => {
+                        let root = u.rational.nth_root(2)?;
+                        solutions.push((-root.clone()).into());
+                        solutions.push(root.into());
+                    }
+                    (surd, _) => {
+                        let u = Solution { surd, ..u }.approximate();
+                        let root = if u >= 0. {
+                            Data::Float(u.sqrt())
+                        } else {
+                            Complex::new(Data::Int(0), Data::Float((-u).sqrt())).into()
+                        };
+                        solutions.push((-root.clone()).into());
+                        solutions.push(root.into());
+                    }
+                }
+            }
+        }
+        _ => {
+            let floats = coeffs
+                .into_iter()
+                .map(f64::try_from)
+                .collect::<Result<Vec<_>, _>>()?;
+            solutions.extend(real_roots(&floats).into_iter().map(|x| Data::Float(x).into()))
+        }
+    }
+    Ok(solutions)
+}
+
+/// `ax^2 + bx + c = 0`, by the quadratic formula
+fn quadratic(a: &Data, b: &Data, c: &Data) -> Result<Vec<Solution>, Error> {
+    let discriminant = ((b.clone() * b.clone())? - ((Data::Int(4) * a.clone())? * c.clone())?)?;
+    let two_a = (Data::Int(2) * a.clone())?;
+    let vertex = ((-b.clone()) / two_a.clone())?;
+    if discriminant == Data::Int(0) {
+        return Ok(vec![vertex.into()]);
+    } else if discriminant < Data::Int(0) {
+        // a pair of complex roots, -b/2a ± (√(4ac - b^2)/2a)i
+        let offset = match (exact_ratio(&-discriminant.clone()), exact_ratio(&two_a)) {
+            (Some(d), Some(two_a)) => match square_root(d)? {
+                Data::Radical(r) => {
+                    Data::Radical(Radical::new_raw(r.coefficient / big(two_a), r.index, r.radicand))
+                }
+                rational => (rational / Data::from_ratio(two_a))?,
+            },
+            _ => (Data::Float(f64::try_from(-discriminant)?.sqrt()) / two_a)?,
+        };
+        return Ok(vec![
+            Data::from(Complex::new(vertex.clone(), -offset.clone())).into(),
+            Data::from(Complex::new(vertex, offset)).into(),
+        ]);
+    }
+    let root = match (exact_ratio(&discriminant), exact_ratio(&two_a)) {
+        (Some(d), Some(two_a)) => match square_root(d)? {
+            // x = -b/2a ± (k/2a)√m
+            Data::Radical(r) => {
+                let surd = Radical::new_raw(r.coefficient / big(two_a), r.index, r.radicand);
+                let negated = Radical::new_raw(-surd.coefficient.clone(), surd.index, surd.radicand.clone());
+                return Ok(vec![
+                    Solution {
+                        rational: vertex.clone(),
+                        surd: Some(negated),
+                    },
+                    Solution {
+                        rational: vertex,
+                        surd: Some(surd),
+                    },
+                ]);
+            }
+            rational => rational,
+        },
+        _ => Data::Float(f64::try_from(discriminant)?.sqrt()),
+    };
+    let offset = (root / two_a)?;
+    Ok(vec![
+        (vertex.clone() - offset.clone())?.into(),
+        (vertex + offset)?.into(),
+    ])
+}
+
+fn exact_ratio(d: &Data) -> Option<Ratio<i64>> {
+    match d {
+        Data::Int(n) => Some(Ratio::from(*n)),
+        Data::Rational(r) => Some(*r),
+        _ => None,
+    }
+}
+
+/// Every `p/q` where `p` divides the constant term and `q` divides the leading coefficient,
+/// which are the only rational numbers that can be roots. Empty if the coefficients aren't all rational
+fn rational_candidates(coeffs: &[Data]) -> Vec<Data> {
+    let ratios = coeffs.iter().map(exact_ratio).collect::<Option<Vec<_>>>();
+    let ratios = match ratios {
+        Some(r) => r,
+        None => return vec![],
+    };
+    // scale up to whole numbers
+    let scale = ratios.iter().fold(1, |acc, r| lcm(acc, *r.denom()));
+    let whole = |r: &Ratio<i64>| r.numer().checked_mul(scale / r.denom());
+    let (constant, leading) = match (whole(&ratios[0]), whole(&ratios[ratios.len() - 1])) {
+        (Some(c), Some(l)) => (c, l),
+        _ => return vec![],
+    };
+    let mut candidates = Vec::new();
+    for p in divisors(constant) {
+        for q in divisors(leading) {
+            for sign in [1, -1] {
+                let candidate = Data::from_ratio(Ratio::new(sign * p, q));
+                if !candidates.contains(&candidate) {
+                    candidates.push(candidate);
+                }
+            }
+        }
+    }
+    candidates
+}
+
+/// the positive divisors of `n`, as long as it's small enough to find them in a reasonable time
+fn divisors(n: i64) -> Vec<i64> {
+    let n = n.abs();
+    if n > 1_000_000_000_000 {
+        return vec![];
+    }
+    let mut result = Vec::new();
+    let mut d = 1;
+    while d * d <= n {
+        if n % d == 0 {
+            result.push(d);
+            if d * d != n {
+                result.push(n / d);
+            }
+        }
+        d += 1;
+    }
+    result
+}
+
+/// the value of the polynomial at `x`
+fn horner(coeffs: &[Data], x: &Data) -> Result<Data, Error> {
+    coeffs
+        .iter()
+        .rev()
+        .try_fold(Data::Int(0), |acc, c| (acc * x.clone())? + c.clone())
+}
+
+/// divides the polynomial by `(x - root)`, which needs to go into it exactly
+fn deflate(coeffs: &[Data], root: &Data) -> Result<Vec<Data>, Error> {
+    let mut quotient = vec![coeffs[coeffs.len() - 1].clone()];
+    for c in coeffs[1..coeffs.len() - 1].iter().rev() {
+        let next = ((quotient[quotient.len() - 1].clone() * root.clone())? + c.clone())?;
+        quotient.push(next);
+    }
+    quotient.reverse();
+    Ok(quotient)
+}
+
+/// The real roots of a polynomial, found numerically. Between each pair of turning points
+/// (which are the roots of the derivative) the polynomial can only cross zero once, so bisection finds it
+fn real_roots(coeffs: &[f64]) -> Vec<f64> {
+    let degree = coeffs.len() - 1;
+    if degree == 1 {
+        return vec![-coeffs[0] / coeffs[1]];
+    }
+    let value = |x: f64| coeffs.iter().rev().fold(0., |acc, c| acc * x + c);
+    let derivative: Vec<f64> = coeffs
+        .iter()
+        .enumerate()
+        .skip(1)
+        .map(|(power, c)| power as f64 * c)
+        .collect();
+    // every root is smaller than this, in size
+    let bound = 1. + coeffs[..degree]
+        .iter()
+        .map(|c| (c / coeffs[degree]).abs())
+        .fold(0., f64::max);
+    let turning_points = real_roots(&derivative);
+    let mut points = vec![-bound];
+    points.extend(turning_points.iter().copied());
+    points.push(bound);
+
+    let mut roots = Vec::new();
+    for pair in points.windows(2) {
+        let (mut low, mut high) = (pair[0], pair[1]);
+        if value(low).signum() == value(high).signum() {
+            continue;
+        }
+        for _ in 0..200 {
+            let middle = (low + high) / 2.;
+            if value(middle).signum() == value(low).signum() {
+                low = middle;
+            } else {
+                high = middle;
+            }
+        }
+        roots.push((low + high) / 2.);
+    }
+    // roots that only touch zero, like x^4, happen at turning points
+    for x in turning_points {
+        if value(x).abs() < 1e-12 && !roots.iter().any(|r| (r - x).abs() < 1e-9) {
+            roots.push(x);
+        }
+    }
+    roots.sort_by(|a, b| a.partial_cmp(b).unwrap_or(std::cmp::Ordering::Equal));
+    roots
+}
+
+#[cfg(test)]
+mod test {
+    use crate::eval::Environment;
+    use crate::parser::parse_statement;
+
+    fn solve(input: &str) -> String {
+        let mut env = Environment::new();
+        format!("{}", parse_statement(input).unwrap().execute(&mut env).unwrap())
+    }
+
+    #[test]
+    fn linear() {
+        assert_eq!(solve("solve 3*x + 2 = 11"), "x = 3");
+        assert_eq!(solve("solve 2*y = 1"), "y = 1/2");
+    }
+
+    #[test]
+    fn quadratics() {
+        assert_eq!(solve("solve x^2 - 2*x - 1 = 0"), "x = 1 - √2 or x = 1 + √2");
+        assert_eq!(solve("solve x^2 = 4"), "x = -2 or x = 2");
+        assert_eq!(solve("solve x^2 + 3*x = 0"), "x = -3 or x = 0");
+        assert_eq!(solve("solve x(x + 1) = 0"), "x = -1 or x = 0");
+        assert_eq!(solve("solve 2*x^2 = 3"), "x = -(1/2)√6 or x = (1/2)√6");
+        assert_eq!(solve("solve x^2 + 1 = 0"), "x = -i or x = i");
+        assert_eq!(solve("solve x^2 + 2*x + 5 = 0"), "x = -1 - 2i or x = -1 + 2i");
+    }
+
+    #[test]
+    fn variables_with_values_are_still_solved_for() {
+        let mut env = Environment::new();
+        let mut run = |line: &str| format!("{}", parse_statement(line).unwrap().execute(&mut env).unwrap());
+        run("let x = 3");
+        run("let a = 2");
+        assert_eq!(run("solve x^2 = 4"), "x = -2 or x = 2");
+        assert_eq!(run("solve a*x = 4, x"), "x = 2");
+        assert_eq!(run("solve a*y = 4"), "y = 2");
+        assert_eq!(run("x"), "3");
+    }
+
+    #[test]
+    fn higher_degrees() {
+        assert_eq!(solve("solve x^3 - 6*x^2 + 11*x - 6 = 0"), "x = 1 or x = 2 or x = 3");
+        assert_eq!(solve("solve x^3 = 2*x"), "x = -√2 or x = 0 or x = √2");
+        assert_eq!(solve("solve x^5 + x = 3"), "x = 1.13299757");
+        assert_eq!(
+            solve("solve x^4 - 5*x^2 + 6 = 0"),
+            "x = -√3 or x = -√2 or x = √2 or x = √3"
+        );
+    }
+
+    #[test]
+    fn complex_solutions_of_quadratics_in_x_squared() {
+        assert_eq!(
+            solve("solve x^4 + 1 = 0"),
+            "x = (-1/2)√2 + (1/2)i√2 or x = (1/2)√2 - (1/2)i√2 or x = (-1/2)√2 - (1/2)i√2 or x = (1/2)√2 + (1/2)i√2"
+        );
+        assert_eq!(solve("solve x^4 + 5*x^2 + 4 = 0"), "x = -2i or x = 2i or x = -i or x = i");
+    }
+}
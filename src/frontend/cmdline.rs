@@ -1,53 +1,81 @@
 /*! This is the commandline frontend for matheriser, which waits for input and is singlethreaded, doesn't automagically change the expressions you enter */
 
+use crate::error::Error;
+use crate::eval::Environment;
+use super::line_editor::LineEditor;
 use std::collections::HashMap;
+use std::path::PathBuf;
+
+mod commands;
 
 /// The information struct for the frontend
-pub struct CommandLine<'m, 'k: 'm> {
+pub struct CommandLine {
     preamble: bool,
-    manifest: &'m HashMap<&'k str, String>,
+    /// everything the frontend says, in the language it's speaking
+    manifest: HashMap<String, String>,
+    environment: Environment,
+    editor: LineEditor,
 }
-impl<'m, 'k> CommandLine<'m, 'k> {
+impl CommandLine {
     const PROMPT_TEXT: &'static str = "matherise";
 
-    pub fn new(manifest: &'m HashMap<&'k str, String>) -> Self {
+    /// `history_file` is where the lines typed in are kept between runs
+    pub fn new(manifest: HashMap<String, String>, environment: Environment, history_file: Option<PathBuf>) -> Self {
         CommandLine {
             preamble: true,
-            manifest: manifest,
+            manifest,
+            environment,
+            editor: LineEditor::new(history_file),
         }
     }
+
+    /// the line called `key` in the localisation file, which is blank if this language doesn't have it
+    fn text(&self, key: &str) -> &str {
+        self.manifest.get(key).map_or("", String::as_str)
+    }
 }
 
-use crate::parser::parse_string;
+use crate::parser::parse_statement;
 use colored::Colorize;
-use dialoguer::{theme::ColorfulTheme, Input};
-impl<'m, 'k> super::Frontend for CommandLine<'_, '_> {
-    fn run(&mut self) -> Result<(), String> {
+use commands::Flow;
+impl super::Frontend for CommandLine {
+    fn run(&mut self) -> Result<(), Error> {
         //println!("began to run");
         if self.preamble {
             let coloured_preamble = self
                 .manifest
                 .get("preamble") // get the preamble
-                .ok_or("couldn't get the preamble")? // unwrap it
+                .ok_or_else(|| Error::Frontend("couldn't get the preamble".into()))? // unwrap it
                 .green();
             println!("{}", coloured_preamble)
             //println!("preambled");
         }
         loop {
-            let input_result: Result<String, _> = Input::with_theme(&ColorfulTheme::default())
-                .with_prompt(CommandLine::PROMPT_TEXT)
-                .interact_text();
-            let input = match input_result {
-                Ok(s) => s,
-                Err(_) => {
-                    return Err("Couldn't get your input".into());
-                }
+            let prompt = format!("{} {} ", CommandLine::PROMPT_TEXT.bold(), "›".dimmed());
+            let input = match self.editor.read_line(&prompt)? {
+                Some(input) => input,
+                None => break,
+            };
+            if input.trim().is_empty() {
+                continue;
+            }
+            let outcome = match input.trim().strip_prefix('!') {
+                Some(command) => commands::run(self, command),
+                None => parse_statement(&input)
+                    .and_then(|x| x.execute(&mut self.environment))
+                    .map(|outcome| {
+                        self.environment.remember(&input, &outcome);
+                        println!("         >=> {}", outcome);
+                        Flow::Continue
+                    }),
             };
-            if input == "!qt" {
-                break;
+            match outcome {
+                Ok(Flow::Continue) => {}
+                Ok(Flow::Quit) => break,
+                // a mistake only costs the line it's on, and the line is only known in here,
+                // so this is where it gets pointed at
+                Err(why) => eprintln!("{}", super::coloured_error(&why, &input)),
             }
-            let out_text = parse_string(&input).and_then(|x| x.eval())?;
-            println!("         >=> {}", out_text);
         }
         Ok(())
     }
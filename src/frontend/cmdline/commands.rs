@@ -0,0 +1,182 @@
+/*! The `!commands`, which do things to the session rather than being maths, like `!vars` or `!mode deg`.
+Adding one is adding a function here and a line to `COMMANDS`, and its help goes in the localisation files */
+
+use super::CommandLine;
+use crate::error::Error;
+use crate::frontend::{float_precision, set_float_precision};
+use std::fmt::Display;
+
+/// What the command line does once a command is done
+pub enum Flow {
+    Continue,
+    Quit,
+}
+
+type CommandResult = Result<Flow, Error>;
+
+struct Command {
+    name: &'static str,
+    /// this gets whatever was written after the name, trimmed
+    run: fn(&mut CommandLine, &str) -> CommandResult,
+}
+
+/// Every command, in the order `!help` lists them. The help for `!name` is `help-name` in the localisation file
+const COMMANDS: &[Command] = &[
+    Command { name: "help", run: help },
+    Command { name: "vars", run: vars },
+    Command { name: "funcs", run: funcs },
+    Command { name: "history", run: history },
+    Command { name: "clear", run: clear },
+    Command { name: "mode", run: mode },
+    Command { name: "decimals", run: decimals },
+    Command { name: "precision", run: precision },
+    Command { name: "save", run: save },
+    Command { name: "load", run: load },
+    Command { name: "lang", run: lang },
+    Command { name: "qt", run: quit },
+];
+
+/// Floats only have about 17 digits in them, any more would just be showing off their rounding errors
+const MOST_DIGITS: usize = 17;
+
+/// Runs `line`, which is a command with the `!` taken off the front, like `mode deg`
+pub fn run(cl: &mut CommandLine, line: &str) -> CommandResult {
+    let (name, rest) = match line.find(char::is_whitespace) {
+        Some(i) => line.split_at(i),
+        None => (line, ""),
+    };
+    (find(name)?.run)(cl, rest.trim())
+}
+
+fn find(name: &str) -> Result<&'static Command, Error> {
+    COMMANDS
+        .iter()
+        .find(|command| command.name == name)
+        .ok_or_else(|| Error::Frontend(format!("there's no command called !{}, try !help", name)))
+}
+
+fn say(what: impl Display) -> CommandResult {
+    println!("         >=> {}", what);
+    Ok(Flow::Continue)
+}
+
+/// `!help` lists every command, `!help mode` is just the one
+fn help(cl: &mut CommandLine, about: &str) -> CommandResult {
+    let about = about.trim_start_matches('!');
+    let commands = if about.is_empty() {
+        println!("{}", cl.text("help"));
+        COMMANDS.iter().collect()
+    } else {
+        vec![find(about)?]
+    };
+    for command in commands {
+        println!("  !{:<10} {}", command.name, cl.text(&format!("help-{}", command.name)));
+    }
+    Ok(Flow::Continue)
+}
+
+fn vars(cl: &mut CommandLine, _: &str) -> CommandResult {
+    let variables = cl.environment.variables();
+    if variables.is_empty() {
+        return say("there aren't any variables yet");
+    }
+    for (name, value) in variables {
+        println!("  {} = {}", name, value);
+    }
+    Ok(Flow::Continue)
+}
+
+fn funcs(cl: &mut CommandLine, _: &str) -> CommandResult {
+    let functions = cl.environment.functions();
+    if functions.is_empty() {
+        return say("there aren't any functions yet");
+    }
+    for (name, function) in functions {
+        println!("  {}({}) = {}", name, function.params.join(", "), function.body);
+    }
+    Ok(Flow::Continue)
+}
+
+fn history(cl: &mut CommandLine, _: &str) -> CommandResult {
+    for (n, (line, value)) in cl.environment.history().iter().enumerate() {
+        println!("  ${:<4} {}  >=> {}", n + 1, line, value);
+    }
+    Ok(Flow::Continue)
+}
+
+fn clear(cl: &mut CommandLine, _: &str) -> CommandResult {
+    cl.environment.clear();
+    say("forgot every variable, function and earlier result")
+}
+
+/// on its own this says what the mode is, and with a mode after it, it changes it
+fn mode(cl: &mut CommandLine, mode: &str) -> CommandResult {
+    if !mode.is_empty() {
+        cl.environment.set_angle_mode(mode.parse()?);
+    }
+    say(format!("angles are in {}", cl.environment.angle_mode()))
+}
+
+fn decimals(cl: &mut CommandLine, mode: &str) -> CommandResult {
+    if !mode.is_empty() {
+        cl.environment.set_decimal_mode(mode.parse()?);
+    }
+    say(format!("decimals are read as {}", cl.environment.decimal_mode()))
+}
+
+fn precision(_: &mut CommandLine, digits: &str) -> CommandResult {
+    if !digits.is_empty() {
+        match digits.parse() {
+            Ok(digits) if digits <= MOST_DIGITS => set_float_precision(digits),
+            _ => {
+                return Err(Error::invalid_argument(format!(
+                    "the precision is a number of digits from 0 to {}, not {}",
+                    MOST_DIGITS, digits
+                )))
+            }
+        }
+    }
+    say(format!("floats are shown to {} digits after the point", float_precision()))
+}
+
+/// writes the variables, functions and history out to a file
+fn save(cl: &mut CommandLine, file: &str) -> CommandResult {
+    let text = ron::ser::to_string_pretty(&cl.environment.session(), ron::ser::PrettyConfig::new())
+        .map_err(|e| Error::Frontend(format!("couldn't write the session out -- {}", e)))?;
+    std::fs::write(file, text).map_err(|e| Error::Frontend(format!("couldn't save to {} -- {}", file, e)))?;
+    say(format!("saved to {}", file))
+}
+
+/// reads back in a session that `!save` wrote out
+fn load(cl: &mut CommandLine, file: &str) -> CommandResult {
+    let text = std::fs::read_to_string(file)
+        .map_err(|e| Error::Frontend(format!("couldn't load {} -- {}", file, e)))?;
+    let session = ron::de::from_str(&text)
+        .map_err(|e| Error::Frontend(format!("{} isn't a saved session -- {}", file, e)))?;
+    cl.environment.restore(session);
+    say(format!("loaded {}", file))
+}
+
+fn lang(cl: &mut CommandLine, language: &str) -> CommandResult {
+    cl.manifest = crate::get_localisation(language).map_err(Error::Frontend)?;
+    say(cl.text("language"))
+}
+
+fn quit(_: &mut CommandLine, _: &str) -> CommandResult {
+    Ok(Flow::Quit)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use std::collections::HashMap;
+
+    #[test]
+    fn every_command_has_help() {
+        let english: HashMap<String, String> = ron::de::from_str(include_str!("../../../assets/en-uk.ron")).unwrap();
+        for command in COMMANDS {
+            assert!(english.contains_key(&format!("help-{}", command.name)), "!{}", command.name);
+        }
+        assert!(find("bogus").is_err());
+    }
+}
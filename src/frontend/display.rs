@@ -1,41 +1,305 @@
 /*! These are all the display implementations for `Data`*/
 
-use crate::eval::{radical::Radical, Data, Symbolic};
+use crate::eval::{
+    complex::Complex, logarithm::Logarithm, polynomial::Polynomial, primes::Factorisation, radical::Radical, radical_sum::RadicalSum, solve::Solution,
+    Data, Outcome, Symbolic,
+};
+use num::traits::{One, Signed};
+use crate::error::{Error, Span};
+use crate::parser::{BinaryOp, ExprTree, UnaryOp};
+use colored::{Color, Colorize};
 use std::fmt;
 use std::fmt::{Display, Formatter};
+use std::sync::atomic::{self, AtomicUsize};
+
+/// How many digits after the point floats are shown with, which `!precision` changes
+static FLOAT_PRECISION: AtomicUsize = AtomicUsize::new(8);
+
+pub fn float_precision() -> usize {
+    FLOAT_PRECISION.load(atomic::Ordering::Relaxed)
+}
+
+pub fn set_float_precision(digits: usize) {
+    FLOAT_PRECISION.store(digits, atomic::Ordering::Relaxed)
+}
 
 impl Display for Data {
     fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
         match &self {
             Data::Int(a) => write!(f, "{}", a),
             Data::Float(a) => {
+                let digits = float_precision();
                 let maybe_scientific =
-                    if format!("{:.8}", a).matches('0').collect::<Vec<_>>().len() >= 4 {
-                        format!("{:.8e}", a)
+                    if format!("{:.*}", digits, a).matches('0').collect::<Vec<_>>().len() >= 4 {
+                        format!("{:.*e}", digits, a)
                     } else {
-                        format!("{:.8}", a)
+                        format!("{:.*}", digits, a)
                     };
                 write!(f, "{}", maybe_scientific)
             }
             Data::Rational(a) => write!(f, "{}/{}", a.numer(), a.denom()),
+            Data::BigInt(a) => write!(f, "{}", a),
+            Data::BigRational(a) => write!(f, "{}/{}", a.numer(), a.denom()),
             Data::Radical(a) => {
-                let coeff = if a.coefficient == 1.into() {
+                let coeff = if a.coefficient.is_one() {
                     "".to_string()
-                } else if *a.coefficient.denom() == 1 {
+                } else if (-&a.coefficient).is_one() {
+                    "-".to_string()
+                } else if a.coefficient.is_integer() {
                     a.coefficient.numer().to_string()
                 } else {
-                    format!("{}", Data::Rational(a.coefficient))
+                    format!("({})", Data::from(a.coefficient.clone()))
                 };
-                let index = a.index;
-                let index_str = if index == 2 {
-                    "".to_string()
-                } else {
-                    index.to_string()
+                let root = match a.index {
+                    2 => "√".to_string(),
+                    3 => "∛".to_string(),
+                    4 => "∜".to_string(),
+                    n => format!("{}√", superscript(n)),
                 };
-                write!(f, "({}) {}√({})", coeff, index_str, a.radicand)
+                match *a.radicand {
+                    Data::Int(n) if n >= 0 => write!(f, "{}{}{}", coeff, root, n),
+                    ref radicand => write!(f, "{}{}({})", coeff, root, radicand),
+                }
             }
             Data::Symbol(a) => write!(f, "{}", a.as_utf8()),
             Data::Symbolic(a) => write!(f, "{}", a),
+            Data::Polynomial(a) => write!(f, "{}", a),
+            Data::Complex(a) => write!(f, "{}", a),
+            Data::RadicalSum(a) => write!(f, "{}", a),
+            Data::Log(a) => write!(f, "{}", a),
+            Data::Factorisation(a) => write!(f, "{}", a),
+        }
+    }
+}
+
+impl Display for Complex {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        let negative = self.im < Data::Int(0);
+        let im = if negative { -self.im.clone() } else { self.im.clone() };
+        match (self.re == Data::Int(0), negative) {
+            (true, false) => {}
+            (true, true) => write!(f, "-")?,
+            (false, false) => write!(f, "{} + ", self.re)?,
+            (false, true) => write!(f, "{} - ", self.re)?,
+        }
+        match im {
+            Data::Int(1) => write!(f, "i"),
+            Data::Int(_) | Data::BigInt(_) | Data::Float(_) => write!(f, "{}i", im),
+            // the i goes in front of the root, `2i√3`, so it doesn't look like it's under it
+            Data::Radical(r) => {
+                match Data::from(r.coefficient.clone()) {
+                    Data::Int(1) => {}
+                    Data::Int(n) => write!(f, "{}", n)?,
+                    coeff => write!(f, "({})", coeff)?,
+                }
+                write!(f, "i{}", Data::Radical(Radical { coefficient: One::one(), ..r.clone() }))
+            }
+            _ => write!(f, "({})i", im),
+        }
+    }
+}
+
+impl Display for RadicalSum {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        let rational = Some(self.rational.clone()).filter(|r| *r != Data::Int(0));
+        let terms = rational
+            .into_iter()
+            .chain(self.radicals.iter().cloned().map(Data::Radical));
+        for (i, term) in terms.enumerate() {
+            let negative = term < Data::Int(0);
+            match (i, negative) {
+                (0, true) => write!(f, "-")?,
+                (0, false) => {}
+                (_, true) => write!(f, " - ")?,
+                (_, false) => write!(f, " + ")?,
+            }
+            write!(f, "{}", if negative { -term } else { term })?;
+        }
+        Ok(())
+    }
+}
+
+impl Display for Logarithm {
+    /// written the way it would be typed in, and `-log(2)` rather than `log(1/2)`
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        if self.argument < Data::Int(1) {
+            return write!(f, "-{}", self.clone().neg());
+        }
+        match &self.base {
+            Data::Int(10) => write!(f, "log({})", self.argument),
+            Data::Symbol(e) if e == "e" || e == "E" => write!(f, "ln({})", self.argument),
+            base => write!(f, "log({}, {})", self.argument, base),
+        }
+    }
+}
+
+impl Display for Polynomial {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        for (i, (monomial, coeff)) in self.terms().into_iter().enumerate() {
+            let negative = coeff < &Data::Int(0);
+            match (i, negative) {
+                (0, true) => write!(f, "-")?,
+                (0, false) => {}
+                (_, true) => write!(f, " - ")?,
+                (_, false) => write!(f, " + ")?,
+            }
+            let coeff = if negative { -coeff.clone() } else { coeff.clone() };
+            if monomial.is_empty() {
+                write!(f, "{}", coeff)?;
+                continue;
+            }
+            match coeff {
+                Data::Int(1) => {}
+                // anything with a slash or a space in it needs brackets to not run into the variables
+                Data::Int(_) | Data::BigInt(_) | Data::Float(_) | Data::Symbol(_) => {
+                    write!(f, "{}", coeff)?
+                }
+                _ => write!(f, "({})", coeff)?,
+            }
+            for (variable, power) in monomial {
+                match power {
+                    1 => write!(f, "{}", variable)?,
+                    _ => write!(f, "{}^{}", variable, power)?,
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+/// the index of a root, written small, like ⁵√
+fn superscript(n: u32) -> String {
+    n.to_string()
+        .chars()
+        .map(|c| match c {
+            '0' => '⁰',
+            '1' => '¹',
+            '2' => '²',
+            '3' => '³',
+            '4' => '⁴',
+            '5' => '⁵',
+            '6' => '⁶',
+            '7' => '⁷',
+            '8' => '⁸',
+            _ => '⁹',
+        })
+        .collect()
+}
+
+impl Display for Outcome {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            Outcome::Value(data) => write!(f, "{}", data),
+            Outcome::Defined(name, params) => write!(f, "defined {}({})", name, params.join(", ")),
+            Outcome::Expression(tree) => write!(f, "{}", tree),
+            Outcome::Solved(var, roots) if roots.is_empty() => {
+                write!(f, "there are no real solutions for {}", var)
+            }
+            Outcome::Solved(var, roots) => {
+                for (i, root) in roots.iter().enumerate() {
+                    if i > 0 {
+                        write!(f, " or ")?;
+                    }
+                    write!(f, "{} = {}", var, root)?;
+                }
+                Ok(())
+            }
+        }
+    }
+}
+
+impl Display for Solution {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        let surd = match &self.surd {
+            None => return write!(f, "{}", self.rational),
+            Some(surd) => surd,
+        };
+        let negative = surd.coefficient.is_negative();
+        let magnitude = Data::Radical(Radical {
+            coefficient: surd.coefficient.abs(),
+            ..surd.clone()
+        });
+        match (self.rational == Data::Int(0), negative) {
+            (true, false) => write!(f, "{}", magnitude),
+            (true, true) => write!(f, "-{}", magnitude),
+            (false, false) => write!(f, "{} + {}", self.rational, magnitude),
+            (false, true) => write!(f, "{} - {}", self.rational, magnitude),
+        }
+    }
+}
+
+/// How tightly something binds when it's written out, the same as the parser's precedences,
+/// so that only the brackets that are needed get written
+fn display_precedence(tree: &ExprTree) -> u8 {
+    match tree {
+        ExprTree::BNode(op, _, _) => op.precedence(),
+        ExprTree::UNode(op, _) => op.precedence(),
+        ExprTree::Val(data) => match data {
+            Data::Polynomial(p) if p.terms().len() > 1 => 1,
+            Data::Symbolic(s) if s.constant.is_some() => 1,
+            d if d < &Data::Int(0) => UnaryOp::Neg.precedence(),
+            // a lone `x` or `x^2`
+            Data::Polynomial(p) => match p.terms().as_slice() {
+                [(monomial, Data::Int(1))] if monomial.len() == 1 => match monomial.values().next() {
+                    Some(1) => u8::MAX,
+                    _ => BinaryOp::Exp.precedence(),
+                },
+                _ => BinaryOp::Mul.precedence(),
+            },
+            Data::Rational(_) | Data::BigRational(_) | Data::Radical(_) | Data::Symbolic(_) => {
+                BinaryOp::Mul.precedence()
+            }
+            _ => u8::MAX,
+        },
+        ExprTree::Ident(_) | ExprTree::Call(_, _) => u8::MAX,
+    }
+}
+
+/// writes `tree`, in brackets if it binds looser than `outer` (or just as loosely, if `tie` says so)
+fn write_operand(f: &mut Formatter<'_>, tree: &ExprTree, outer: u8, tie: bool) -> fmt::Result {
+    let inner = display_precedence(tree);
+    if inner < outer || (inner == outer && tie) {
+        write!(f, "({})", tree)
+    } else {
+        write!(f, "{}", tree)
+    }
+}
+
+impl Display for ExprTree {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            ExprTree::Val(data) => write!(f, "{}", data),
+            ExprTree::Ident(name) => write!(f, "{}", name),
+            ExprTree::Call(name, args) => {
+                write!(f, "{}(", name)?;
+                for (i, arg) in args.iter().enumerate() {
+                    if i > 0 {
+                        write!(f, ", ")?;
+                    }
+                    write!(f, "{}", arg)?;
+                }
+                write!(f, ")")
+            }
+            ExprTree::UNode(op, t) if op.is_postfix() => {
+                write_operand(f, t, op.precedence(), true)?;
+                write!(f, "{}", op.symbol())
+            }
+            ExprTree::UNode(op, t) => {
+                write!(f, "{}", op.symbol())?;
+                write_operand(f, t, op.precedence(), true)
+            }
+            ExprTree::BNode(op, lhs, rhs) => {
+                let outer = op.precedence();
+                write_operand(f, lhs, outer, *op == BinaryOp::Exp)?;
+                match op {
+                    BinaryOp::Plus => write!(f, " + ")?,
+                    BinaryOp::Minus => write!(f, " - ")?,
+                    BinaryOp::Mul => write!(f, " * ")?,
+                    BinaryOp::Div => write!(f, " / ")?,
+                    BinaryOp::Exp => write!(f, "^")?,
+                }
+                write_operand(f, rhs, outer, matches!(op, BinaryOp::Minus | BinaryOp::Div))
+            }
         }
     }
 }
@@ -80,6 +344,40 @@ struct FactorChain {
     data_factors: Vec<DFactor>,
 }
 
+/// `2^3 × 3 × 5^2`, every factor to its power
+fn stringify_factor_chain(input: &[DFactor]) -> String {
+    input
+        .iter()
+        .map(|factor| match (&factor.val, factor.exponent) {
+            (val, 1) => format!("{}", val),
+            (val @ (Data::Int(_) | Data::BigInt(_) | Data::Symbol(_)), exponent) => {
+                format!("{}^{}", val, exponent)
+            }
+            (val, exponent) => format!("({})^{}", val, exponent),
+        })
+        .collect::<Vec<_>>()
+        .join(" × ")
+}
+
+impl Display for Factorisation {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        let factors = self
+            .factors
+            .iter()
+            .map(|(p, k)| DFactor {
+                val: Data::from(p.clone()),
+                exponent: *k,
+            })
+            .collect::<Vec<_>>();
+        let sign = if self.negative { "-" } else { "" };
+        if factors.is_empty() {
+            write!(f, "{}1", sign)
+        } else {
+            write!(f, "{}{}", sign, stringify_factor_chain(&factors))
+        }
+    }
+}
+
 /// this function primarily exists because I cannot be bothered writing a trait
 fn insert_or_inc_factor(factors: &mut Vec<DFactor>, insert: Data) {
     let is_one = insert == Data::Int(1);
@@ -127,14 +425,18 @@ impl FactorChain {
 
     fn add(&mut self, data: Data) {
         match data {
-            Data::Int(_) | Data::Float(_) | Data::Rational(_) => {
+            Data::Int(_)
+            | Data::Float(_)
+            | Data::Rational(_)
+            | Data::BigInt(_)
+            | Data::BigRational(_) => {
                 insert_or_inc_factor(&mut self.data_factors, data)
             }
             Data::Symbol(s) => insert_or_inc_symbol(&mut self.symbol_map, s.as_utf8()),
             Data::Radical(rad) => {
-                let coeff = Data::Rational(rad.coefficient);
+                let coeff = Data::from(rad.coefficient);
                 let rest = Data::Radical(Radical {
-                    coefficient: 1.into(),
+                    coefficient: One::one(),
                     ..rad
                 });
                 insert_or_inc_factor(&mut self.data_factors, coeff);
@@ -148,6 +450,10 @@ impl FactorChain {
                     insert_or_inc_factor(&mut self.data_factors, Data::Symbolic(s))
                 }
             }
+            Data::Polynomial(_) | Data::Complex(_) | Data::RadicalSum(_) | Data::Log(_) => {
+                insert_or_inc_factor(&mut self.data_factors, data)
+            }
+            Data::Factorisation(f) => self.add(Data::from(f.value())),
         }
     }
 
@@ -251,23 +557,6 @@ impl Display for Symbolic {
         let l_symbols = stringify_symbol_chain(&l_symbol_factors); // as a string
         let r_symbols = stringify_symbol_chain(&r_symbol_factors);
 
-        fn stringify_factor_chain(input: &[DFactor]) -> String {
-            if input.len() == 1 {
-                format!("{}", input[0].val)
-            } else {
-                let mut output = String::new();
-                for i in input.iter().rev() {
-                    if Some(i) == input.last() {
-                        output.push_str(format!("{} × ", i.val).as_str())
-                    } else if Some(i) == input.first() {
-                        output.push_str(format!("({})^{}", i.val, i.exponent).as_str())
-                    } else {
-                        output.push_str(format!("({})^{} × ", i.val, i.exponent).as_str())
-                    }
-                }
-                output
-            }
-        }
         let l_factors = stringify_factor_chain(&l_factor_chain.data_factors);
         let r_factors = stringify_factor_chain(&r_factor_chain.data_factors);
         if r_factors == "" && r_symbols == "" {
@@ -278,6 +567,41 @@ impl Display for Symbolic {
     }
 }
 
+/// Errors for the terminal, with the kind in bold so you can tell at a glance whether you
+/// mistyped something (yellow) or asked for something impossible (red).
+/// If we know where in `input` it went wrong, that gets pointed out underneath
+pub fn coloured_error(error: &Error, input: &str) -> String {
+    let colour = match error {
+        Error::Parse { .. }
+        | Error::UnknownFunction(_)
+        | Error::UnknownConstant(_)
+        | Error::Arity { .. } => Color::Yellow,
+        _ => Color::Red,
+    };
+    let mut out = format!(
+        "{} {}",
+        format!("{}:", error.kind()).color(colour).bold(),
+        error.message().color(colour)
+    );
+    if let Some(span) = error.span() {
+        out.push('\n');
+        out.push_str(&underline(input, span).color(colour).to_string());
+    }
+    out
+}
+
+/// The input with carets under `span`, like
+/// ```text
+///     2 * (3 + 4
+///         ^
+/// ```
+/// the end of the line gets a caret just past the last character
+pub fn underline(input: &str, span: Span) -> String {
+    let start = input.get(..span.start).map_or(0, |s| s.chars().count());
+    let width = input.get(span).map_or(0, |s| s.chars().count()).max(1);
+    format!("    {}\n    {}{}", input, " ".repeat(start), "^".repeat(width))
+}
+
 #[cfg(test)]
 mod test {
 
@@ -294,4 +618,11 @@ mod test {
             "π"
         )
     }
+    #[test]
+    fn underlines_by_character() {
+        assert_eq!(underline("1 + 2 *", 6..7), "    1 + 2 *\n          ^");
+        // π is two bytes but only one column
+        assert_eq!(underline("π + foo", 5..8), "    π + foo\n        ^^^");
+        assert_eq!(underline("1 +", 3..3), "    1 +\n       ^");
+    }
 }
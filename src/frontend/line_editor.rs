@@ -0,0 +1,242 @@
+/*! Reading lines typed in at the terminal. The arrow keys move along the line and go back through earlier lines,
+and Ctrl-R searches back through them. Earlier lines are kept in a file, so they're still there the next time */
+
+use crate::error::Error;
+use console::{Key, Term};
+use std::fs::{self, OpenOptions};
+use std::io::{self, BufRead, Write};
+use std::path::PathBuf;
+
+/// Only this many of the most recent lines are kept from one run to the next
+const HISTORY_LENGTH: usize = 1_000;
+
+pub struct LineEditor {
+    term: Term,
+    /// every line that's been entered, oldest first
+    history: Vec<String>,
+    /// where the history is kept between runs, if there's anywhere to keep it
+    file: Option<PathBuf>,
+}
+
+/// A line as it's being typed
+#[derive(Clone, Default)]
+struct Line {
+    text: Vec<char>,
+    /// how many `char`s into `text` the cursor is
+    cursor: usize,
+}
+
+impl Line {
+    fn from(text: &str) -> Self {
+        let text: Vec<char> = text.chars().collect();
+        Line {
+            cursor: text.len(),
+            text,
+        }
+    }
+}
+
+impl LineEditor {
+    pub fn new(file: Option<PathBuf>) -> Self {
+        let mut history: Vec<String> = file
+            .as_ref()
+            .and_then(|file| fs::read_to_string(file).ok())
+            .map(|text| text.lines().map(String::from).collect())
+            .unwrap_or_default();
+        let forgotten = history.len().saturating_sub(HISTORY_LENGTH);
+        if forgotten > 0 {
+            history.drain(..forgotten);
+            if let Some(file) = &file {
+                // losing the history isn't worth stopping over, so this and the appending below can fail quietly
+                let _ = fs::write(file, history.join("\n") + "\n");
+            }
+        }
+        LineEditor {
+            term: Term::stdout(),
+            history,
+            file,
+        }
+    }
+
+    /// Reads a line, which goes into the history if there's anything on it.
+    /// `None` means there's nothing more to read, because of Ctrl-D or the end of the input
+    pub fn read_line(&mut self, prompt: &str) -> Result<Option<String>, Error> {
+        let line = if self.term.is_term() {
+            self.edit(prompt)
+        } else {
+            read_plain()
+        }
+        .map_err(|e| Error::Frontend(format!("couldn't get your input -- {}", e)))?;
+        if let Some(line) = &line {
+            self.remember(line);
+        }
+        Ok(line)
+    }
+
+    fn remember(&mut self, line: &str) {
+        let line = line.trim();
+        if line.is_empty() || self.history.last().map(String::as_str) == Some(line) {
+            return;
+        }
+        self.history.push(line.to_string());
+        if let Some(file) = &self.file {
+            if let Ok(mut file) = OpenOptions::new().create(true).append(true).open(file) {
+                let _ = writeln!(file, "{}", line);
+            }
+        }
+    }
+
+    /// Ctrl-C throws the line away and starts a fresh one, and Ctrl-D on an empty line gives back `None`
+    fn edit(&self, prompt: &str) -> io::Result<Option<String>> {
+        let _ignoring = IgnoreInterrupts::new();
+        let mut line = Line::default();
+        // how far back through the history we are, `history.len()` is the line being typed
+        let mut back = self.history.len();
+        // what was being typed before going back through the history
+        let mut typed = Line::default();
+        // what's being searched for, while Ctrl-R is searching
+        let mut search: Option<String> = None;
+        loop {
+            match &search {
+                Some(query) => self.render(&format!("(search) `{}`: ", query), &line)?,
+                None => self.render(prompt, &line)?,
+            }
+            let key = match self.term.read_key() {
+                Err(e) if e.kind() == io::ErrorKind::Interrupted => Key::Char('\x03'),
+                key => key?,
+            };
+            if let Some(query) = &mut search {
+                // each letter searches again from the line that was found, and Ctrl-R from the one before it
+                let before = match key {
+                    Key::Char('\x12') => Some(back),
+                    Key::Char(c) if !c.is_control() => {
+                        query.push(c);
+                        Some((back + 1).min(self.history.len()))
+                    }
+                    Key::Backspace => {
+                        query.pop();
+                        None
+                    }
+                    Key::Escape => {
+                        search = None;
+                        continue;
+                    }
+                    // anything else stops searching, and then does what it would normally do
+                    _ => {
+                        search = None;
+                        None
+                    }
+                };
+                if let Some(before) = before {
+                    if let Some(found) = self.find(search.as_deref().unwrap_or(""), before) {
+                        back = found;
+                        line = Line::from(&self.history[found]);
+                    }
+                    continue;
+                }
+                if search.is_some() {
+                    continue;
+                }
+            }
+            match key {
+                Key::Enter => {
+                    self.term.write_line("")?;
+                    return Ok(Some(line.text.into_iter().collect()));
+                }
+                Key::Char('\x03') => {
+                    self.term.move_cursor_right(line.text.len() - line.cursor)?;
+                    self.term.write_line("^C")?;
+                    line = Line::default();
+                    back = self.history.len();
+                }
+                Key::Char('\x04') if line.text.is_empty() => {
+                    self.term.write_line("")?;
+                    return Ok(None);
+                }
+                Key::Char('\x04') | Key::Del if line.cursor < line.text.len() => {
+                    line.text.remove(line.cursor);
+                }
+                Key::Char('\x12') => search = Some(String::new()),
+                Key::Char(c) if !c.is_control() => {
+                    line.text.insert(line.cursor, c);
+                    line.cursor += 1;
+                }
+                Key::Backspace if line.cursor > 0 => {
+                    line.cursor -= 1;
+                    line.text.remove(line.cursor);
+                }
+                Key::ArrowLeft if line.cursor > 0 => line.cursor -= 1,
+                Key::ArrowRight if line.cursor < line.text.len() => line.cursor += 1,
+                Key::Home => line.cursor = 0,
+                Key::End => line.cursor = line.text.len(),
+                Key::ArrowUp if back > 0 => {
+                    if back == self.history.len() {
+                        typed = line;
+                    }
+                    back -= 1;
+                    line = Line::from(&self.history[back]);
+                }
+                Key::ArrowDown if back < self.history.len() => {
+                    back += 1;
+                    line = match self.history.get(back) {
+                        Some(earlier) => Line::from(earlier),
+                        None => typed.clone(),
+                    };
+                }
+                _ => {}
+            }
+        }
+    }
+
+    /// the latest line from before `before` with `query` in it
+    fn find(&self, query: &str, before: usize) -> Option<usize> {
+        (0..before).rev().find(|i| self.history[*i].contains(query))
+    }
+
+    /// redraws the line, with the cursor where it should be
+    fn render(&self, prompt: &str, line: &Line) -> io::Result<()> {
+        self.term.clear_line()?;
+        let text: String = line.text.iter().collect();
+        self.term.write_str(&format!("{}{}", prompt, text))?;
+        self.term.move_cursor_left(line.text.len() - line.cursor)
+    }
+}
+
+/// console raises SIGINT itself when it reads a Ctrl-C, which would end the whole program,
+/// so it's ignored for as long as one of these is around, which is while a line is being typed
+#[cfg(unix)]
+struct IgnoreInterrupts(libc::sighandler_t);
+
+#[cfg(unix)]
+impl IgnoreInterrupts {
+    fn new() -> Self {
+        IgnoreInterrupts(unsafe { libc::signal(libc::SIGINT, libc::SIG_IGN) })
+    }
+}
+
+#[cfg(unix)]
+impl Drop for IgnoreInterrupts {
+    fn drop(&mut self) {
+        unsafe { libc::signal(libc::SIGINT, self.0) };
+    }
+}
+
+/// elsewhere Ctrl-C comes through as a key without any signals
+#[cfg(not(unix))]
+struct IgnoreInterrupts;
+
+#[cfg(not(unix))]
+impl IgnoreInterrupts {
+    fn new() -> Self {
+        IgnoreInterrupts
+    }
+}
+
+/// When the input isn't coming from a terminal, like when it's piped in, there's nothing to edit
+fn read_plain() -> io::Result<Option<String>> {
+    let mut line = String::new();
+    if io::stdin().lock().read_line(&mut line)? == 0 {
+        return Ok(None);
+    }
+    Ok(Some(line.trim_end_matches(&['\r', '\n'][..]).to_string()))
+}
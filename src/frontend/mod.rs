@@ -1,9 +1,11 @@
 mod display;
 mod cmdline;
+mod line_editor;
+use crate::error::Error;
 pub use display::*;
 
 pub use cmdline::CommandLine;
 
 pub trait Frontend {
-    fn run(&mut self) -> Result<(), String>;
+    fn run(&mut self) -> Result<(), Error>;
 }
@@ -1,20 +1,22 @@
 #![feature(negative_impls)]
 #![feature(drain_filter)]
 
+mod error;
 mod eval;
 mod frontend;
 mod parser;
 mod util;
 
 use colored::Colorize;
-use std::fs::File;
+use std::fs::{self, File};
 use std::io::prelude::*;
 use std::path::Path;
 
 use ron::de::from_str;
 use std::collections::HashMap;
+use eval::{AngleMode, DecimalMode, Environment};
 use frontend::{CommandLine, Frontend};
-use parser::parse_string;
+use parser::parse_statement;
 
 use structopt::StructOpt;
 
@@ -41,7 +43,15 @@ struct Options {
     immediate: Option<String>,
 
     #[structopt(short, long, default_value = "en-uk")]
-    language: String
+    language: String,
+
+    /// The unit trig functions use for angles: rad, deg or grad
+    #[structopt(short, long, default_value = "rad")]
+    angle: AngleMode,
+
+    /// How decimals like 0.1 are read: exact, as fractions, or float
+    #[structopt(short, long, default_value = "exact")]
+    decimals: DecimalMode,
 }
 
 fn crash() -> ! {
@@ -51,33 +61,52 @@ fn crash() -> ! {
 
 fn main() {
     let opt = Options::from_args();
-    let mut locbuffer = String::new();
-    let localisation_map = get_localisation(&opt.language, &mut locbuffer);
+    let localisation_map = match get_localisation(&opt.language) {
+        Ok(map) => map,
+        Err(reason) => {
+            eprintln!("{}", format!("Aborting: {}", reason).red());
+            crash()
+        }
+    };
 
     if let Some(expr) = &opt.immediate {
-        let tree = match parse_string(expr) {
-            Err(why) => {
-                eprintln!("{}", format!("{}", why).red());
-                crash()
-            }
-            Ok(tree) => tree
-        };
-        match tree.eval() {
-            Err(why) => {
-                eprintln!("{}", format!("{}", why).red());
-                crash()
-            }
-            Ok(answer) => {
-                println!("{}", format!("{}", answer).blue());
+        // several statements can be given at once, separated by `;`, only the last one's outcome is shown
+        let mut environment = Environment::new();
+        environment.set_angle_mode(opt.angle);
+        environment.set_decimal_mode(opt.decimals);
+        let mut outcome = None;
+        for line in expr.split(';').map(str::trim).filter(|line| !line.is_empty()) {
+            let statement = match parse_statement(line) {
+                Err(why) => {
+                    eprintln!("{}", frontend::coloured_error(&why, line));
+                    crash()
+                }
+                Ok(statement) => statement
+            };
+            match statement.execute(&mut environment) {
+                Err(why) => {
+                    eprintln!("{}", frontend::coloured_error(&why, line));
+                    crash()
+                }
+                Ok(answer) => {
+                    environment.remember(line, &answer);
+                    outcome = Some(answer)
+                }
             }
         }
+        if let Some(answer) = outcome {
+            println!("{}", format!("{}", answer).blue());
+        }
     }
     else {
         match opt.interface {
             FrontendOpt::CommandLine => {
-                let mut frontend = CommandLine::new(&localisation_map);
+                let mut environment = Environment::new();
+                environment.set_angle_mode(opt.angle);
+                environment.set_decimal_mode(opt.decimals);
+                let mut frontend = CommandLine::new(localisation_map, environment, history_file());
                 if let Err(e) = frontend.run() {
-                    eprintln!("{}", format!("{}", e).red())
+                    eprintln!("{}", frontend::coloured_error(&e, ""))
                 }
 
             }
@@ -93,60 +122,30 @@ const LANGUAGES_MANIFEST: &'static str = include_str!("../assets/manifest.ron");
 use directories::ProjectDirs;
 use std::path::PathBuf;
 
-fn get_localisation<'a>(query: &String, locbuffer: &'a mut String) -> HashMap<&'a str, String> {
-    let manifest: Vec<&str> = match from_str(LANGUAGES_MANIFEST) {
-        Err(reason) => {
-            eprintln!("{}", format!("Aborting: could not parse the languages manifest -- {}", reason).red());
-            crash()
-        }
-        Ok(list) => list
-    };
-    if manifest.contains(&&**query) {
-        // let localisation_file_path = Path::new(&format!{"assets/{}.ron", query});
-        let localisation_file_path = match seek_get_localisation_file() {
-            Ok(p) => p,
-            Err(reason) => {
-                eprintln!(
-                    "{}",
-                    format!("Aborting: could not open file -- {}", reason).red()
-                );
-                std::process::exit(1);
-            }
-        };
-        let mut localisation_file = match File::open(localisation_file_path.join(PathBuf::from(format!("{}.ron", query)))) {
-            Err(reason) => {
-                eprintln!(
-                    "{}",
-                    format!("Aborting: could not open file -- {}", reason).red()
-                );
-                std::process::exit(1);
-            }
-            Ok(file) => file,
-        };
-        if let Err(reason) = localisation_file.read_to_string(locbuffer) {
-            eprintln!(
-                "{}",
-                format!("Aborting: could read file -- {}", reason).red()
-            );
-            std::process::exit(1);
-        }
-        // parse it as a hashmap
-        let manifest: HashMap<&str, String> = match from_str(locbuffer) {
-            Err(e) => {
-                eprintln!(
-                    "{}",
-                    format!("Developer error: localisation file is bad -- {}", e).red()
-                );
-                std::process::exit(1);
-            }
-            Ok(map) => map,
-        };
-        manifest
-    }
-    else {
-        eprintln!("Aborting: language {} not found", query);
-        crash()
+/// Reads in everything the frontend says, in `language`. `!lang` uses this too, to switch languages part way through
+fn get_localisation(language: &str) -> Result<HashMap<String, String>, String> {
+    let manifest: Vec<&str> = from_str(LANGUAGES_MANIFEST)
+        .map_err(|reason| format!("could not parse the languages manifest -- {}", reason))?;
+    if !manifest.contains(&language) {
+        return Err(format!("language {} not found", language));
     }
+    let localisation_file_path =
+        seek_get_localisation_file().map_err(|reason| format!("could not open file -- {}", reason))?;
+    let mut localisation_file = File::open(localisation_file_path.join(PathBuf::from(format!("{}.ron", language))))
+        .map_err(|reason| format!("could not open file -- {}", reason))?;
+    let mut buffer = String::new();
+    localisation_file
+        .read_to_string(&mut buffer)
+        .map_err(|reason| format!("could not read file -- {}", reason))?;
+    // parse it as a hashmap
+    from_str(&buffer).map_err(|e| format!("developer error, the localisation file is bad -- {}", e))
+}
+
+/// Where the lines typed into the command line are kept between runs, making the directory for it if it isn't there
+fn history_file() -> Option<PathBuf> {
+    let dir = ProjectDirs::from("", "", "matheriser")?.data_local_dir().to_path_buf();
+    fs::create_dir_all(&dir).ok()?;
+    Some(dir.join("history"))
 }
 
 fn seek_get_localisation_file() -> Result<PathBuf, String> {
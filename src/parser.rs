@@ -1,5 +1,10 @@
 /* parsing is done by recursive descent */
+use crate::error::{Error, Span};
 use logos::Logos;
+use num::bigint::BigInt;
+use num::rational::BigRational;
+use serde::{Deserialize, Serialize};
+use std::fmt;
 use std::mem;
 
 #[derive(Logos, Debug, PartialEq, Clone)]
@@ -8,68 +13,145 @@ enum Token {
     #[error]
     Error,
 
-    /// A number literal
+    /// A number literal, as big as it's written
     #[regex(r"[0-9]+", |lex| lex.slice().parse())]
-    INumber(i64),
-    #[regex(r"[0-9]+\.[0-9]+", |lex| lex.slice().parse())]
-    #[regex(r"\.[0-9]+", |lex| {["0", lex.slice()].concat().parse()})]
-    FNumber(f64),
+    INumber(BigInt),
+    /// `1.5`, `.5`, `1.5e-3` or `2e10`, kept as written until it's made into an exact fraction
+    #[regex(r"[0-9]+\.[0-9]+([eE][+-]?[0-9]+)?", |lex| lex.slice().to_string())]
+    #[regex(r"\.[0-9]+([eE][+-]?[0-9]+)?", |lex| lex.slice().to_string())]
+    #[regex(r"[0-9]+[eE][+-]?[0-9]+", |lex| lex.slice().to_string())]
+    Decimal(String),
 
-    #[regex(r"[\(\)\*\+-/\^]", |lex| lex.slice().chars().nth(0))]
+    #[regex(r"[\(\)\*\+-/\^=]", |lex| lex.slice().chars().next())]
     Operator(char),
 
+    /// `!`, `!!` and `%`, which go after the thing they apply to
+    #[token("!", |_| UnaryOp::Factorial)]
+    #[token("!!", |_| UnaryOp::DoubleFactorial)]
+    #[token("%", |_| UnaryOp::Percent)]
+    Postfix(UnaryOp),
+
+    /// `√`, `∛` and `∜`, with the root they take, which go before the thing they apply to
+    #[token("√", |_| 2)]
+    #[token("∛", |_| 3)]
+    #[token("∜", |_| 4)]
+    Radix(i64),
+
+    /// `:=`, the other way to write `let`
+    #[token(":=")]
+    Assign,
+
     #[regex(r":[a-zA-Z]+", |lex| {
         let thing = lex.slice()[1..].to_string();
         thing
     })]
     Symbol(String),
 
-    #[regex(r"[a-zA-Z]+", |lex| lex.slice().to_string())]
+    /// the greek constants are words on their own, so `2πr` is 2 × π × r
+    #[regex(r"[a-zA-Z][a-zA-Z0-9]*", |lex| lex.slice().to_string())]
+    #[regex(r"[πτϕφ]", |lex| lex.slice().to_string())]
     Word(String),
 
+    /// `log_2`, a logarithm with its base written on, which goes before what it applies to just like `log`
+    #[regex(r"log_[0-9]+", |lex| lex.slice()[4..].parse().ok())]
+    LogBase(i64),
+
+    /// `$3` or `$-1`, an earlier result
+    #[regex(r"\$-?[0-9]+", |lex| lex.slice().to_string())]
+    History(String),
+
     EOF,
 }
 
-fn tokenise(stringin: &str) -> Vec<Token> {
-    let mut r: Vec<Token> = Token::lexer(stringin).collect();
-    r.push(Token::EOF);
+/// How tokens are written in error messages
+impl fmt::Display for Token {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Token::Error => write!(f, "something I don't understand"),
+            Token::INumber(n) => write!(f, "`{}`", n),
+            Token::Decimal(n) => write!(f, "`{}`", n),
+            Token::Operator(c) => write!(f, "`{}`", c),
+            Token::Postfix(op) => write!(f, "`{}`", op.symbol()),
+            Token::Radix(2) => write!(f, "`√`"),
+            Token::Radix(3) => write!(f, "`∛`"),
+            Token::Radix(_) => write!(f, "`∜`"),
+            Token::Assign => write!(f, "`:=`"),
+            Token::Symbol(s) => write!(f, "`:{}`", s),
+            Token::Word(w) => write!(f, "`{}`", w),
+            Token::LogBase(base) => write!(f, "`log_{}`", base),
+            Token::History(name) => write!(f, "`{}`", name),
+            Token::EOF => write!(f, "the end of the line"),
+        }
+    }
+}
+
+/// the tokens backwards, so the parser can pop them off the end, each with where it came from
+fn tokenise(stringin: &str) -> Vec<(Token, Span)> {
+    let mut r: Vec<(Token, Span)> = Token::lexer(stringin).spanned().collect();
+    r.push((Token::EOF, stringin.len()..stringin.len()));
     r.reverse();
     r
 }
 
 struct Parser {
     current: Token,
-    stack: Vec<Token>,
+    /// where `current` is in the input
+    span: Span,
+    stack: Vec<(Token, Span)>,
+    /// whether the token before current was a `)`, which is all implicit multiplication needs to look back at
+    after_bracket: bool,
 }
 
 impl Parser {
-    fn new(tok: Vec<Token>) -> Self {
+    fn new(tok: Vec<(Token, Span)>) -> Self {
         let mut tok = tok;
-        // println!("tokens are {:?}", tok);
+        let (current, span) = tok.pop().unwrap_or((Token::EOF, 0..0));
         Parser {
-            current: tok.pop().unwrap_or(Token::EOF), // should this be Err, Maybe
+            current,
+            span,
             stack: tok,
+            after_bracket: false,
         }
     }
     ///puts the next element of the stack into current
-    fn next(&mut self) -> Result<(), String> {
+    fn next(&mut self) -> Result<(), Error> {
+        self.pop().map(|_| ())
+    }
+
+    fn pop(&mut self) -> Result<Token, Error> {
         match self.stack.pop() {
-            Some(t) => {
-                self.current = t;
-                Ok(())
+            Some((t, span)) => {
+                self.span = span;
+                let popped = mem::replace(&mut self.current, t);
+                self.after_bracket = popped == Token::Operator(')');
+                Ok(popped)
             }
-            None => Err("got to the end of the file while still parsing".to_string()),
+            None => Err(self.error("the line ended too soon")),
         }
     }
 
-    fn pop(&mut self) -> Result<Token, String> {
-        match self.stack.pop() {
-            Some(t) => {
-                let mut res = t;
-                mem::swap(&mut self.current, &mut res);
-                Ok(res)
-            }
-            None => Err("got to the end of the file while still parsing".to_string()),
+    /// looks at the token after current, without moving
+    fn peek(&self) -> &Token {
+        self.stack.last().map(|(t, _)| t).unwrap_or(&Token::EOF)
+    }
+
+    /// the tokens after current, in order
+    fn upcoming(&self) -> impl Iterator<Item = &Token> {
+        self.stack.iter().rev().map(|(t, _)| t)
+    }
+
+    /// an error pointing at current
+    fn error(&self, message: impl Into<String>) -> Error {
+        error_at(self.span.clone(), message)
+    }
+
+    /// an error for when current isn't something that can go here
+    fn unexpected(&self) -> Error {
+        match &self.current {
+            Token::EOF => self.error("the line ended too soon"),
+            Token::Operator(')') => self.error("unmatched `)`"),
+            Token::Error => self.error("this isn't something I understand"),
+            other => self.error(format!("didn't expect {} here", other)),
         }
     }
 
@@ -86,7 +168,6 @@ impl Parser {
         result
     }
 
-    #[allow(dead_code)] // We're going to use this later to do parsing of custom functions I think
     fn is_word(&self) -> bool {
         match self.current {
             Token::Word(_) => true,
@@ -94,9 +175,9 @@ impl Parser {
         }
     }
 
-    // fn expect(&self, tok: &Token) -> Result<(), String> {
+    // fn expect(&self, tok: &Token) -> Result<(), Error> {
     //     if !(self.current == *tok) {
-    //         Err(format!("Expected token {:?}, got {:?}", self.current, tok))
+    //         Err(Error::parse(format!("Expected token {:?}, got {:?}", self.current, tok)))
     //     } else {
     //         Ok(())
     //     }
@@ -111,35 +192,65 @@ impl Parser {
         }
     }
 
-    fn require(&mut self, tok: Token) -> Result<Token, String> {
+    fn require(&mut self, tok: Token) -> Result<Token, Error> {
         if self.consume(&tok) {
             Ok(tok)
         } else {
-            Err(format!("Required token {:?}, got {:?}", self.current, tok))
+            Err(self.error(format!("expected {}, but got {}", tok, self.current)))
         }
     }
 }
 
-#[derive(Debug, Eq, PartialEq)]
+fn error_at(span: Span, message: impl Into<String>) -> Error {
+    Error::Parse {
+        message: message.into(),
+        span: Some(span),
+    }
+}
+
+#[derive(Clone, Debug, Eq, PartialEq, Serialize, Deserialize)]
 pub enum UnaryOp {
     Neg,
-    Word(String),
+    /// `n!`
+    Factorial,
+    /// `n!!`
+    DoubleFactorial,
+    /// `x%`
+    Percent,
 }
 impl UnaryOp {
-    fn from(tok: &Token) -> Result<UnaryOp, String> {
+    fn from(tok: &Token) -> Result<UnaryOp, Error> {
         match tok {
             Token::Operator('-') => Ok(UnaryOp::Neg),
-            Token::Word(bla) => Ok(UnaryOp::Word(bla.clone())),
-            _ => Err("Unexpected token parsing unary operator".to_string()),
+            Token::Postfix(op) => Ok(op.clone()),
+            _ => Err(Error::parse("Unexpected token parsing unary operator")),
+        }
+    }
+
+    /// The postfix operators bind tighter than anything else, so `2^3!` is `2^6` and `-3!` is `-6`
+    pub fn precedence(&self) -> u8 {
+        match self {
+            Self::Neg => 2,
+            Self::Factorial | Self::DoubleFactorial | Self::Percent => 5,
         }
     }
 
-    fn precedence(&self) -> u8 {
-        2
+    /// does this go after the thing it applies to
+    pub fn is_postfix(&self) -> bool {
+        !matches!(self, Self::Neg)
+    }
+
+    pub fn symbol(&self) -> &'static str {
+        match self {
+            Self::Neg => "-",
+            Self::Factorial => "!",
+            Self::DoubleFactorial => "!!",
+            Self::Percent => "%",
+        }
     }
 }
 
-#[derive(Debug, Eq, PartialEq)]
+#[derive(Clone, Debug, Eq, PartialEq, Serialize, Deserialize)]
 pub enum BinaryOp {
     Plus,
     Minus,
@@ -149,7 +260,7 @@ pub enum BinaryOp {
 }
 
 impl BinaryOp {
-    fn from(tok: &Token) -> Result<Self, String> {
+    fn from(tok: &Token) -> Result<Self, Error> {
         match tok {
             Token::Operator(c) => Ok(match c {
                 '+' => BinaryOp::Plus,
@@ -157,13 +268,13 @@ impl BinaryOp {
                 '/' => BinaryOp::Div,
                 '*' => BinaryOp::Mul,
                 '^' => BinaryOp::Exp,
-                _ => return Err(format!("'{}' is not recognised as a binary operator", c)),
+                _ => return Err(Error::parse(format!("'{}' is not recognised as a binary operator", c))),
             }),
-            _ => Err(String::from("Unexpected token parsing unary operator")),
+            _ => Err(Error::parse("Unexpected token parsing unary operator")),
         }
     }
 
-    fn precedence(&self) -> u8 {
+    pub fn precedence(&self) -> u8 {
         match self {
             Self::Plus | Self::Minus => 1,
             Self::Mul | Self::Div => 3,
@@ -172,21 +283,29 @@ impl BinaryOp {
     }
 }
 
-use crate::eval::Data;
-#[derive(Debug, PartialEq)]
+use crate::eval::{constant, is_constant, is_function, Data};
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
 pub enum ExprTree {
     Val(Data),
+    /// a variable, looked up when the tree is evaluated
+    Ident(String),
+    /// a call to a function, built in or user-defined, with its arguments
+    Call(String, Vec<ExprTree>),
     UNode(UnaryOp, Box<ExprTree>),
     BNode(BinaryOp, Box<ExprTree>, Box<ExprTree>),
 }
 
 impl ExprTree {
-    fn make_leaf(tok: &Token) -> Result<Self, String> {
+    fn make_leaf(tok: &Token) -> Result<Self, Error> {
         match tok {
-            Token::INumber(n) => Ok(ExprTree::Val((*n as i64).into())),
-            Token::FNumber(n) => Ok(ExprTree::Val((*n as f64).into())),
-            Token::Symbol(n) => Ok(ExprTree::Val((&*n.clone() as &str).to_string().into())),
-            _ => Err("Tried to parse something that isn't a number as a number".to_string()),
+            // small enough numbers are made back into `Int`s
+            Token::INumber(n) => Ok(ExprTree::Val(n.clone().into())),
+            Token::Decimal(n) => Ok(ExprTree::Val(decimal(n)?)),
+            // `:x` isn't a constant we know of, so it's a variable like any other
+            Token::Symbol(n) => Ok(constant(n).map_or_else(|| ExprTree::Ident(n.clone()), ExprTree::Val)),
+            // looked up like any other variable
+            Token::History(name) => Ok(ExprTree::Ident(name.clone())),
+            _ => Err(Error::parse("Tried to parse something that isn't a number as a number")),
         }
     }
     fn make_unary_node(op: UnaryOp, tree: ExprTree) -> ExprTree {
@@ -197,27 +316,240 @@ impl ExprTree {
     }
 }
 
-fn parse_expression(p: &mut Parser) -> Result<ExprTree, String> {
+/// Exponents past this would make numbers with more digits than anyone could want
+const BIGGEST_DECIMAL_EXPONENT: i64 = 10_000;
+
+/// A decimal literal as the exact fraction it's written as, `1.25e-3` is 125 × 10^(-5) = 1/800
+fn decimal(text: &str) -> Result<Data, Error> {
+    let (mantissa, exponent) = match text.find(|c| c == 'e' || c == 'E') {
+        Some(i) => (&text[..i], &text[i + 1..]),
+        None => (text, "0"),
+    };
+    let (whole, fraction) = mantissa.split_once('.').unwrap_or((mantissa, ""));
+    let exponent = exponent
+        .parse::<i64>()
+        .ok()
+        .filter(|e| e.abs() <= BIGGEST_DECIMAL_EXPONENT)
+        .ok_or_else(|| Error::Overflow(format!("the exponent of {} is too big", text)))?;
+    let digits: BigInt = [whole, fraction].concat().parse().unwrap(); // the lexer only lets digits through
+    let ten = BigInt::from(10);
+    let shift = exponent - fraction.len() as i64;
+    Ok(Data::from(if shift >= 0 {
+        BigRational::from_integer(digits * ten.pow(shift as u32))
+    } else {
+        BigRational::new(digits, ten.pow(-shift as u32))
+    }))
+}
+
+/// A whole line of input, which is either something to work out or something to remember
+#[derive(Debug, PartialEq)]
+pub enum Statement {
+    Expr(ExprTree),
+    /// `let x = ...` or `x := ...`
+    Assign(String, ExprTree),
+    /// `f(x, y) = ...`, the name, the parameters and the body
+    Define(String, Vec<String>, ExprTree),
+    /// `d/dx ...` or `diff(..., x)`, the expression and the variable
+    Differentiate(ExprTree, String),
+    /// `solve ... = ...`, maybe with `, x` on the end to say what to solve for
+    Solve(ExprTree, ExprTree, Option<String>),
+}
+
+fn parse_statement_inner(p: &mut Parser) -> Result<Statement, Error> {
+    if looks_like_definition(p) {
+        let name = parse_assignee(p)?;
+        p.require(Token::Operator('('))?;
+        let mut params = Vec::new();
+        while let (span, Token::Word(param)) = (p.span.clone(), p.pop()?) {
+            if params.contains(&param) {
+                return Err(error_at(span, format!("the parameter {} is used twice in {}", param, name)));
+            }
+            if is_constant(&param) {
+                return Err(error_at(span, format!("{} can't be a parameter, it's a constant", param)));
+            }
+            params.push(param);
+            p.consume(&Token::Operator(','));
+        }
+        p.require(Token::Operator('='))?;
+        Ok(Statement::Define(name, params, parse_expression(p)?))
+    } else if p.test(&Token::Word("let".to_string())) {
+        p.next()?;
+        let name = parse_assignee(p)?;
+        p.require(Token::Operator('='))?;
+        Ok(Statement::Assign(name, parse_expression(p)?))
+    } else if let Some(var) = derivative_variable(p) {
+        // past the `d`, the `/` and the `dx`
+        for _ in 0..3 {
+            p.next()?;
+        }
+        Ok(Statement::Differentiate(parse_expression(p)?, var))
+    } else if p.test(&Token::Word("diff".to_string())) && p.peek() == &Token::Operator('(') {
+        let start = p.span.start;
+        p.next()?;
+        let mut args = parse_arguments(p)?;
+        if p.current != Token::EOF {
+            return Err(p.unexpected());
+        }
+        match (args.pop(), args.pop(), args.pop()) {
+            (Some(ExprTree::Ident(var)), Some(expr), None) => Ok(Statement::Differentiate(expr, var)),
+            _ => Err(error_at(
+                start..p.span.start,
+                "diff takes an expression and the variable to differentiate by, like diff(x^2, x)",
+            )),
+        }
+    } else if p.test(&Token::Word("solve".to_string())) {
+        p.next()?;
+        let lhs = recognise(0, p)?;
+        p.require(Token::Operator('='))?;
+        let rhs = recognise(0, p)?;
+        let var = if p.consume(&Token::Operator(',')) {
+            let span = p.span.clone();
+            match p.pop()? {
+                Token::Word(var) => Some(var),
+                other => return Err(error_at(span, format!("expected a variable to solve for, but got {}", other))),
+            }
+        } else {
+            None
+        };
+        if p.current != Token::EOF {
+            return Err(p.unexpected());
+        }
+        Ok(Statement::Solve(lhs, rhs, var))
+    } else if let (Token::Word(_), Token::Assign) = (&p.current, p.peek()) {
+        let name = parse_assignee(p)?;
+        p.require(Token::Assign)?;
+        Ok(Statement::Assign(name, parse_expression(p)?))
+    } else {
+        parse_expression(p).map(Statement::Expr)
+    }
+}
+
+/// is the line shaped like `name(a, b, c) = ...`
+fn looks_like_definition(p: &Parser) -> bool {
+    let mut upcoming = p.upcoming();
+    if !p.is_word() || upcoming.next() != Some(&Token::Operator('(')) {
+        return false;
+    }
+    loop {
+        match upcoming.next() {
+            Some(Token::Word(_)) => match upcoming.next() {
+                Some(Token::Operator(',')) => continue,
+                Some(Token::Operator(')')) => break,
+                _ => return false,
+            },
+            Some(Token::Operator(')')) => break,
+            _ => return false,
+        }
+    }
+    upcoming.next() == Some(&Token::Operator('='))
+}
+
+/// `d/dx` at the start of a line is a derivative by x when there's something after it to differentiate,
+/// otherwise it's just d divided by dx. Like with `2 -x`, a `-` after it is a subtraction
+fn derivative_variable(p: &Parser) -> Option<String> {
+    let mut upcoming = p.upcoming();
+    match (&p.current, upcoming.next(), upcoming.next(), upcoming.next()) {
+        (Token::Word(d), Some(Token::Operator('/')), Some(Token::Word(dx)), Some(next))
+            if d == "d"
+                && dx.starts_with('d')
+                && dx[1..].starts_with(|c: char| c.is_ascii_alphabetic())
+                && starts_operand(next)
+                && *next != Token::Operator('-') =>
+        {
+            Some(dx[1..].to_string())
+        }
+        _ => None,
+    }
+}
+
+/// the name on the left of an assignment, which can't be the name of something else
+fn parse_assignee(p: &mut Parser) -> Result<String, Error> {
+    let span = p.span.clone();
+    match p.pop()? {
+        Token::Word(name)
+            if is_function(&name) || is_constant(&name) || ["let", "diff", "solve"].contains(&name.as_str()) =>
+        {
+            Err(error_at(span, format!("can't assign to {}, it already means something", name)))
+        }
+        Token::Word(name) => Ok(name),
+        other => Err(error_at(span, format!("expected a name to assign to, but got {}", other))),
+    }
+}
+
+fn parse_expression(p: &mut Parser) -> Result<ExprTree, Error> {
     let t = recognise(0, p)?;
     if p.current == Token::EOF {
         Ok(t)
     } else {
-        Err("Finished parsing the expression, but there were still things on the stack".to_string())
+        Err(p.unexpected())
     }
 }
 
-fn recognise(n: u8, p: &mut Parser) -> Result<ExprTree, String> {
+/// can an expression start with this token
+fn starts_operand(tok: &Token) -> bool {
+    matches!(
+        tok,
+        Token::Word(_)
+            | Token::INumber(_)
+            | Token::Decimal(_)
+            | Token::Symbol(_)
+            | Token::History(_)
+            | Token::Operator('(')
+            | Token::Operator('-')
+            | Token::Radix(_)
+            | Token::LogBase(_)
+    )
+}
+
+/// Things written next to each other are multiplied, like `2x`, `3(4 + 1)`, `2 sin x` or `(x + 1)(x - 1)`.
+/// This has the same precedence as `*` and `/`, and goes left to right with them just like they do, so
+/// `1/2x` is `(1/2)x` and `2^3x` is `(2^3)x`. Only words, symbols and brackets can be multiplied onto
+/// something like this, and numbers only straight after a bracket like `(1 + 2)3`, so `2 3` is still
+/// a mistake rather than 6, and `2 -x` is still a subtraction
+fn implicit_multiplication(p: &Parser, n: u8) -> bool {
+    let number_after_bracket = p.after_bracket && matches!(p.current, Token::INumber(_) | Token::Decimal(_));
+    (number_after_bracket
+        || matches!(
+            p.current,
+            Token::Word(_)
+                | Token::Symbol(_)
+                | Token::History(_)
+                | Token::Operator('(')
+                | Token::Radix(_)
+                | Token::LogBase(_)
+        ))
+        && BinaryOp::Mul.precedence() >= n
+}
+
+fn recognise(n: u8, p: &mut Parser) -> Result<ExprTree, Error> {
     let mut t = parse_subexpression(p)?;
-    while p.test_set(&[
-        // Is `current` a binary operator?
-        Token::Operator('+'),
-        Token::Operator('-'),
-        Token::Operator('*'),
-        Token::Operator('/'),
-        Token::Operator('^'),
-    ]) && BinaryOp::from(&p.current)?.precedence() >= n
-    {
-        let op = BinaryOp::from(&p.pop()?)?;
+    loop {
+        if let Token::Postfix(_) = p.current {
+            // postfix operators bind tightest, so they always go on whatever is just before them
+            let op = UnaryOp::from(&p.pop()?)?;
+            t = ExprTree::make_unary_node(op, t);
+            continue;
+        }
+        let op = if p.test_set(&[
+            // Is `current` a binary operator?
+            Token::Operator('+'),
+            Token::Operator('-'),
+            Token::Operator('*'),
+            Token::Operator('/'),
+            Token::Operator('^'),
+        ]) && BinaryOp::from(&p.current)?.precedence() >= n
+        {
+            let (op_span, op_token) = (p.span.clone(), p.current.clone());
+            let op = BinaryOp::from(&p.pop()?)?;
+            if !starts_operand(&p.current) {
+                return Err(error_at(op_span, format!("operator {} missing right operand", op_token)));
+            }
+            op
+        } else if implicit_multiplication(p, n) {
+            BinaryOp::Mul
+        } else {
+            break;
+        };
         let q = match op {
             BinaryOp::Exp => op.precedence(), // right associative operators
             _ => op.precedence() + 1,         // everything else is left-associative
@@ -228,35 +560,118 @@ fn recognise(n: u8, p: &mut Parser) -> Result<ExprTree, String> {
     Ok(t)
 }
 
-fn parse_subexpression(p: &mut Parser) -> Result<ExprTree, String> {
-    match p.current {
-        Token::Operator('-') | Token::Word(_) => {
+/// The argument of a function written without brackets, which takes in things multiplied onto it implicitly
+/// but stops at any operator, so `sin 2x` is `sin(2x)` but `sin 2 * x` is `(sin 2) * x` and `sqrt 2 + 1` is
+/// `(sqrt 2) + 1`. Powers still go in, `sin x^2` is `sin(x^2)`
+fn recognise_argument(p: &mut Parser) -> Result<ExprTree, Error> {
+    let q = BinaryOp::Mul.precedence() + 1;
+    let mut t = recognise(q, p)?;
+    while implicit_multiplication(p, BinaryOp::Mul.precedence()) {
+        let t_ = recognise(q, p)?;
+        t = ExprTree::make_binary_node(BinaryOp::Mul, t, t_);
+    }
+    Ok(t)
+}
+
+fn parse_subexpression(p: &mut Parser) -> Result<ExprTree, Error> {
+    match &p.current {
+        Token::Word(_) => match p.pop()? {
+            // constants come first, so `pi(2)` is 2pi rather than a function called pi
+            Token::Word(name) if is_constant(&name) => Ok(ExprTree::Val(constant(&name).unwrap())),
+            Token::Word(name) if p.test(&Token::Operator('(')) => {
+                Ok(ExprTree::Call(name, parse_arguments(p)?))
+            }
+            // built in functions of one argument don't need brackets, like `sin x`
+            Token::Word(name) if is_function(&name) => Ok(ExprTree::Call(name, vec![recognise_argument(p)?])),
+            Token::Word(name) => Ok(ExprTree::Ident(name)),
+            _ => unreachable!(),
+        },
+        Token::Operator('-') => {
             let op = UnaryOp::from(&p.pop()?)?;
             let q = op.precedence();
             Ok(ExprTree::make_unary_node(op, recognise(q, p)?))
         }
+        // `√x` is just another way to write `sqrt x`, and it binds like a power so `√2x` is `(√2)x`
+        Token::Radix(_) => {
+            let index = match p.pop()? {
+                Token::Radix(index) => index,
+                _ => unreachable!(),
+            };
+            let radicand = recognise(BinaryOp::Exp.precedence(), p)?;
+            Ok(match index {
+                2 => ExprTree::Call("sqrt".to_string(), vec![radicand]),
+                3 => ExprTree::Call("cbrt".to_string(), vec![radicand]),
+                n => ExprTree::Call("root".to_string(), vec![radicand, ExprTree::Val(Data::Int(n))]),
+            })
+        }
+        // `log_2 x` is `log(x, 2)`
+        Token::LogBase(_) => {
+            let (span, base) = match (p.span.clone(), p.pop()?) {
+                (span, Token::LogBase(base)) => (span, base),
+                _ => unreachable!(),
+            };
+            let argument = if p.test(&Token::Operator('(')) {
+                match parse_arguments(p)?.as_slice() {
+                    [argument] => argument.clone(),
+                    _ => return Err(error_at(span, format!("log_{} takes one argument, the base is already there", base))),
+                }
+            } else {
+                recognise_argument(p)?
+            };
+            Ok(ExprTree::Call("log".to_string(), vec![argument, ExprTree::Val(Data::Int(base))]))
+        }
         Token::Operator('(') => {
+            let open = p.span.clone();
             let _ = p.next(); // we know this is safe to do because we know current is something
-            let t = recognise(0, p);
-            p.require(Token::Operator(')'))?;
-            t
+            let t = recognise(0, p)?;
+            if !p.consume(&Token::Operator(')')) {
+                return Err(match p.current {
+                    Token::EOF => error_at(open, "unmatched `(`"),
+                    _ => p.error(format!("expected `)` to close the bracket, but got {}", p.current)),
+                });
+            }
+            Ok(t)
         }
-        Token::FNumber(_) | Token::INumber(_) | Token::Symbol(_) => {
+        Token::Decimal(_) | Token::INumber(_) | Token::Symbol(_) | Token::History(_) => {
             Ok(ExprTree::make_leaf(&p.pop()?)?)
         }
-        _ => Err(format!(
-            "Got an unexpected token: {:?}, rest of stack is {:?}",
-            p.current, p.stack
-        )),
+        Token::Operator(c @ ('+' | '*' | '/' | '^')) => {
+            Err(p.error(format!("operator `{}` missing left operand", c)))
+        }
+        Token::Postfix(op) => Err(p.error(format!("operator `{}` missing left operand", op.symbol()))),
+        _ => Err(p.unexpected()),
+    }
+}
+
+/// a bracketed, comma separated list of expressions, like `(1, x + 2)`
+fn parse_arguments(p: &mut Parser) -> Result<Vec<ExprTree>, Error> {
+    p.require(Token::Operator('('))?;
+    let mut arguments = Vec::new();
+    if p.consume(&Token::Operator(')')) {
+        return Ok(arguments);
+    }
+    loop {
+        arguments.push(recognise(0, p)?);
+        if p.consume(&Token::Operator(')')) {
+            return Ok(arguments);
+        }
+        p.require(Token::Operator(','))?;
     }
 }
 
-pub fn parse_string(input: &str) -> Result<ExprTree, String> {
+#[allow(dead_code)] // everything outside of tests parses whole statements, but this is handy for just expressions
+pub fn parse_string(input: &str) -> Result<ExprTree, Error> {
     let toks = tokenise(input);
     let mut parser = Parser::new(toks);
     parse_expression(&mut parser)
 }
 
+pub fn parse_statement(input: &str) -> Result<Statement, Error> {
+    let toks = tokenise(input);
+    let mut parser = Parser::new(toks);
+    parse_statement_inner(&mut parser)
+}
+
 #[cfg(test)]
 mod test {
     use super::*;
@@ -266,6 +681,16 @@ mod test {
         assert_eq!(Ok(ExprTree::Val(3.into())), parse_string("3"))
     }
     #[test]
+    fn correctly_parses_values_past_an_i64() {
+        let big: BigInt = "99999999999999999999999".parse().unwrap();
+        assert_eq!(Ok(ExprTree::Val(big.into())), parse_string("99999999999999999999999"));
+        let smallest = Data::Int(i64::MIN);
+        assert_eq!(
+            parse_string("-9223372036854775808").map(|tree| tree.eval(&crate::eval::Environment::new())),
+            Ok(Ok(smallest))
+        );
+    }
+    #[test]
     fn correctly_parses_simple_tree() {
         assert_eq!(
             Ok(ExprTree::BNode(
@@ -276,6 +701,231 @@ mod test {
             parse_string("3+4")
         )
     }
+    #[test]
+    fn correctly_parses_assignments() {
+        let expected = Ok(Statement::Assign(
+            "x".to_string(),
+            ExprTree::BNode(
+                BinaryOp::Div,
+                ExprTree::Val(3.into()).into(),
+                ExprTree::Val(4.into()).into(),
+            ),
+        ));
+        assert_eq!(expected, parse_statement("let x = 3/4"));
+        assert_eq!(expected, parse_statement("x := 3/4"));
+        assert!(parse_statement("let sin = 3").is_err());
+    }
+    #[test]
+    fn correctly_parses_definitions() {
+        assert_eq!(
+            Ok(Statement::Define(
+                "f".to_string(),
+                vec!["x".to_string(), "y".to_string()],
+                ExprTree::BNode(
+                    BinaryOp::Plus,
+                    ExprTree::Ident("x".to_string()).into(),
+                    ExprTree::Ident("y".to_string()).into()
+                )
+            )),
+            parse_statement("f(x, y) = x + y")
+        );
+        assert_eq!(
+            Ok(Statement::Expr(ExprTree::Call(
+                "f".to_string(),
+                vec![ExprTree::Val(1.into()), ExprTree::Ident("y".to_string())]
+            ))),
+            parse_statement("f(1, y)")
+        );
+        assert!(parse_statement("f(x, x) = x").is_err());
+    }
+    #[test]
+    fn correctly_parses_calls() {
+        assert_eq!(
+            Ok(ExprTree::Call(
+                "log".to_string(),
+                vec![ExprTree::Val(8.into()), ExprTree::Val(2.into())]
+            )),
+            parse_string("log(8, 2)")
+        );
+        assert_eq!(
+            Ok(ExprTree::BNode(
+                BinaryOp::Plus,
+                ExprTree::Call("sin".to_string(), vec![ExprTree::Val(1.into())]).into(),
+                ExprTree::Val(2.into()).into()
+            )),
+            parse_string("sin 1 + 2")
+        );
+    }
+    #[test]
+    fn correctly_parses_variables() {
+        assert_eq!(
+            Ok(Statement::Expr(ExprTree::BNode(
+                BinaryOp::Mul,
+                ExprTree::Ident("x".to_string()).into(),
+                ExprTree::Val(2.into()).into()
+            ))),
+            parse_statement("x * 2")
+        );
+        // symbols that aren't constants are variables too
+        assert_eq!(Ok(ExprTree::Ident("y".to_string())), parse_string(":y"));
+        // so are earlier results
+        assert_eq!(Ok(ExprTree::Ident("$-2".to_string())), parse_string("$-2"));
+        assert_eq!(parse_string("2$1"), parse_string("2 * $1"));
+        assert!(parse_statement("let $1 = 2").is_err());
+    }
+    #[test]
+    fn correctly_parses_derivatives() {
+        let x_squared = ExprTree::BNode(
+            BinaryOp::Exp,
+            ExprTree::Ident("x".to_string()).into(),
+            ExprTree::Val(2.into()).into(),
+        );
+        assert_eq!(
+            Ok(Statement::Differentiate(x_squared.clone(), "x".to_string())),
+            parse_statement("d/dx x^2")
+        );
+        assert_eq!(
+            Ok(Statement::Differentiate(x_squared, "x".to_string())),
+            parse_statement("diff(x^2, x)")
+        );
+        assert!(parse_statement("diff(x^2, 2)").is_err());
+        // without anything after it to differentiate, it's a division
+        assert_eq!(
+            Ok(Statement::Expr(ExprTree::BNode(
+                BinaryOp::Div,
+                ExprTree::Ident("d".to_string()).into(),
+                ExprTree::Ident("dt".to_string()).into(),
+            ))),
+            parse_statement("d/dt")
+        );
+        assert!(matches!(parse_statement("2 + d/dx"), Ok(Statement::Expr(_))));
+        assert!(matches!(parse_statement("d/dx - 1"), Ok(Statement::Expr(_))));
+    }
+    #[test]
+    fn correctly_parses_equations() {
+        assert_eq!(
+            Ok(Statement::Solve(
+                ExprTree::Ident("x".to_string()),
+                ExprTree::Val(2.into()),
+                Some("x".to_string())
+            )),
+            parse_statement("solve x = 2, x")
+        );
+        assert!(parse_statement("solve x + 2").is_err());
+    }
+    #[test]
+    fn correctly_parses_constants() {
+        let pi = Ok(ExprTree::Val(Data::Symbol("pi".to_string())));
+        assert_eq!(parse_string("pi"), pi);
+        assert_eq!(parse_string("π"), pi);
+        assert_eq!(parse_string(":pi"), pi);
+        assert_eq!(parse_string("ϕ"), parse_string("phi"));
+        assert_eq!(parse_string("τ"), parse_string("tau"));
+        assert_eq!(parse_string("2πr"), parse_string("2 * pi * r"));
+        assert_eq!(parse_string("π/4"), parse_string(":pi / 4"));
+        assert!(parse_statement("let e = 2").is_err());
+        assert!(parse_statement("f(pi) = 2").is_err());
+    }
+    #[test]
+    fn implicit_multiplication() {
+        let same = |implicit: &str, explicit: &str| {
+            assert_eq!(parse_string(implicit), parse_string(explicit), "{}", implicit)
+        };
+        same("2x", "2 * x");
+        same("3(4 + 1)", "3 * (4 + 1)");
+        same("(x + 1)(x - 1)", "(x + 1) * (x - 1)");
+        same("2 sin x", "2 * sin(x)");
+        same("2:pi", "2 * :pi");
+        // the same precedence as `*` and `/`, left to right
+        same("1/2x", "(1/2) * x");
+        same("2x/3y", "((2 * x) / 3) * y");
+        // powers still bind tighter
+        same("2x^2", "2 * (x^2)");
+        same("2^3x", "(2^3) * x");
+        // and applying a function takes in implicit multiplication, but stops at any operator
+        same("sin 2x", "sin(2 * x)");
+        same("sin x cos x", "sin(x * cos(x))");
+        same("sin 1 * 2", "sin(1) * 2");
+        same("sqrt 2 * sqrt 3", "sqrt(2) * sqrt(3)");
+        same("sin 2x / 3", "sin(2 * x) / 3");
+        same("sin x^2", "sin(x^2)");
+        same("-2x", "-(2 * x)");
+        same("2 - x", "2 - x");
+        // numbers can go straight after a bracket, but not after another number
+        same("(1 + 2)3", "(1 + 2) * 3");
+        same("(x + 1)2x", "((x + 1) * 2) * x");
+        same("sin(x)2", "sin(x) * 2");
+        assert!(parse_string("2 3").is_err());
+    }
+    #[test]
+    fn decimals_are_exact() {
+        let val = |n: i64, d: i64| Ok(ExprTree::Val(Data::from_ratio((n, d).into())));
+        assert_eq!(parse_string("0.1"), val(1, 10));
+        assert_eq!(parse_string(".25"), val(1, 4));
+        assert_eq!(parse_string("1.5e-3"), val(3, 2000));
+        assert_eq!(parse_string("2E+3"), val(2000, 1));
+        // without digits after it the e is Euler's number
+        assert_eq!(parse_string("2e"), parse_string("2 * e"));
+        assert!(matches!(parse_string("1e100000"), Err(Error::Overflow(_))));
+    }
+    #[test]
+    fn correctly_parses_postfix_operators() {
+        let factorial = |t: ExprTree| ExprTree::UNode(UnaryOp::Factorial, t.into());
+        assert_eq!(parse_string("3!"), Ok(factorial(ExprTree::Val(3.into()))));
+        assert_eq!(
+            parse_string("3!!"),
+            Ok(ExprTree::UNode(UnaryOp::DoubleFactorial, ExprTree::Val(3.into()).into()))
+        );
+        // tighter than everything, even powers and unary minus
+        assert_eq!(parse_string("2^3!"), parse_string("2^(3!)"));
+        assert_eq!(parse_string("-3!"), parse_string("-(3!)"));
+        assert_eq!(parse_string("2x%"), parse_string("2 * (x%)"));
+        assert_eq!(parse_string("(3!)!"), Ok(factorial(factorial(ExprTree::Val(3.into())))));
+        assert!(parse_string("!3").is_err());
+    }
+    #[test]
+    fn correctly_parses_radical_signs() {
+        let same = |radical: &str, call: &str| {
+            assert_eq!(parse_string(radical), parse_string(call), "{}", radical)
+        };
+        same("√2", "sqrt(2)");
+        same("∛x", "cbrt(x)");
+        same("∜16", "root(16, 4)");
+        // they bind like powers, so they take the whole power but nothing multiplied onto it
+        same("√x^2", "sqrt(x^2)");
+        same("√2x", "sqrt(2) * x");
+        same("2√3", "2 * sqrt(3)");
+        same("√-4", "sqrt(-4)");
+        assert!(parse_string("2 + √").is_err());
+    }
+    #[test]
+    fn correctly_parses_log_bases() {
+        let same = |subscript: &str, call: &str| {
+            assert_eq!(parse_string(subscript), parse_string(call), "{}", subscript)
+        };
+        same("log_2 8", "log(8, 2)");
+        same("log_10(x + 1)", "log(x + 1, 10)");
+        same("log_3 9x + 1", "log(9x, 3) + 1");
+        assert!(parse_string("log_2(8, 2)").is_err());
+    }
+    #[test]
+    fn errors_point_at_the_problem() {
+        let error = |input: &str| match parse_statement(input) {
+            Err(Error::Parse { message, span }) => (message, span),
+            other => panic!("expected a parse error, got {:?}", other),
+        };
+        assert_eq!(error("2 * (3 + 4"), ("unmatched `(`".to_string(), Some(4..5)));
+        assert_eq!(error("3 + 4)"), ("unmatched `)`".to_string(), Some(5..6)));
+        assert_eq!(
+            error("1 + 2 *"),
+            ("operator `*` missing right operand".to_string(), Some(6..7))
+        );
+        assert_eq!(
+            error("/ 2"),
+            ("operator `/` missing left operand".to_string(), Some(0..1))
+        );
+        assert_eq!(error("1 +"), ("operator `+` missing right operand".to_string(), Some(2..3)));
+    }
 }
 
 // enum OperatorStackMember {